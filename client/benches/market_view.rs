@@ -0,0 +1,71 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use solana_program::pubkey::Pubkey;
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market, MarketView},
+    types::MarketSettings,
+};
+
+fn get_market_data() -> Vec<u8> {
+    let market = Market {
+        discriminator: MARKET_DISCRIMINATOR,
+        config: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        swap_authority: Some(Pubkey::new_unique()),
+        token_mint0: Pubkey::new_unique(),
+        token_mint1: Pubkey::new_unique(),
+        reserve0: Pubkey::new_unique(),
+        reserve1: Pubkey::new_unique(),
+        fee_reserve: Some(Pubkey::new_unique()),
+        fee_reserve_last_update: 200,
+        settings: MarketSettings {
+            max_supply: 1_000_000_000_000_000,
+            sqrt_price_a_x96: 419236029690706642379639606,
+            sqrt_price_b_x96: 1544441212687274377713657485,
+            liquidity_a: 1 << 100,
+            liquidity_b: 1 << 90,
+            fee: 10_000,
+        },
+        sqrt_price_x96: 419236029690706642379639606,
+        bump: [255],
+    };
+
+    let mut data = borsh::to_vec(&market).unwrap();
+    data.resize(Market::ACCOUNT_LEN, 0);
+
+    data
+}
+
+fn market_deserialization(c: &mut Criterion) {
+    let data = get_market_data();
+
+    let mut group = c.benchmark_group("market_deserialization");
+
+    group.bench_function("from_bytes", |b| {
+        b.iter(|| {
+            let market = Market::from_bytes(black_box(&data)).unwrap();
+            (
+                market.sqrt_price_x96,
+                market.settings,
+                market.token_mint0,
+                market.token_mint1,
+            )
+        })
+    });
+
+    group.bench_function("market_view", |b| {
+        b.iter(|| {
+            let market = MarketView::new(black_box(&data)).unwrap();
+            (
+                market.sqrt_price_x96(),
+                market.settings(),
+                market.token_mint0(),
+                market.token_mint1(),
+            )
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, market_deserialization);
+criterion_main!(benches);
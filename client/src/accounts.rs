@@ -0,0 +1,220 @@
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+pub use super::generated::accounts::*;
+use super::generated::{programs::TOKEN_MILL_V2_ID, types::MarketSettings};
+
+/// First 8 bytes of `sha256("account:Market")`
+pub const MARKET_DISCRIMINATOR: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+/// First 8 bytes of `sha256("account:TokenMillConfig")`
+pub const TOKEN_MILL_CONFIG_DISCRIMINATOR: [u8; 8] = [28, 200, 141, 206, 141, 183, 203, 16];
+
+#[derive(Debug, Error)]
+pub enum AccountError {
+    #[error("account is owned by {actual}, expected the Token Mill V2 program {expected}")]
+    InvalidOwner { expected: Pubkey, actual: Pubkey },
+    #[error("account discriminator {actual:?} doesn't match the expected {expected:?}")]
+    InvalidDiscriminator { expected: [u8; 8], actual: Vec<u8> },
+    #[error("account length {actual} doesn't match the expected {expected}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("account data can't be deserialized: {0}")]
+    Deserialization(#[from] std::io::Error),
+}
+
+impl Market {
+    /// Space allocated by the program for market accounts, larger than the serialized size as options are not always set
+    pub const ACCOUNT_LEN: usize = 527;
+
+    /// Deserializes the data of a market account, checking its discriminator and length
+    pub fn from_account_data(data: &[u8]) -> Result<Self, AccountError> {
+        check_account_data(data, &MARKET_DISCRIMINATOR, Self::ACCOUNT_LEN)?;
+
+        Ok(Self::from_bytes(data)?)
+    }
+
+    /// Same as `from_account_data`, also checking that the account is owned by the program
+    pub fn from_account(owner: &Pubkey, data: &[u8]) -> Result<Self, AccountError> {
+        check_owner(owner)?;
+
+        Self::from_account_data(data)
+    }
+}
+
+impl TokenMillConfig {
+    /// Space allocated by the program for config accounts
+    pub const ACCOUNT_LEN: usize = 316;
+
+    /// Deserializes the data of a config account, checking its discriminator and length
+    pub fn from_account_data(data: &[u8]) -> Result<Self, AccountError> {
+        check_account_data(data, &TOKEN_MILL_CONFIG_DISCRIMINATOR, Self::ACCOUNT_LEN)?;
+
+        Ok(Self::from_bytes(data)?)
+    }
+
+    /// Same as `from_account_data`, also checking that the account is owned by the program
+    pub fn from_account(owner: &Pubkey, data: &[u8]) -> Result<Self, AccountError> {
+        check_owner(owner)?;
+
+        Self::from_account_data(data)
+    }
+}
+
+#[cfg(feature = "anchor")]
+impl anchor_lang::Discriminator for Market {
+    const DISCRIMINATOR: &'static [u8] = &MARKET_DISCRIMINATOR;
+}
+
+#[cfg(feature = "anchor")]
+impl anchor_lang::Discriminator for TokenMillConfig {
+    const DISCRIMINATOR: &'static [u8] = &TOKEN_MILL_CONFIG_DISCRIMINATOR;
+}
+
+/// Zero-copy view over the data of a market account
+/// Only the option tags are read on creation, fields are decoded when accessed
+#[derive(Debug, Clone, Copy)]
+pub struct MarketView<'a> {
+    data: &'a [u8],
+    // Offsets of the fields following the `swap_authority` and `fee_reserve` options
+    token_mint0_offset: usize,
+    fee_reserve_last_update_offset: usize,
+}
+
+impl<'a> MarketView<'a> {
+    const CONFIG_OFFSET: usize = 8;
+    const CREATOR_OFFSET: usize = 40;
+    const SWAP_AUTHORITY_OFFSET: usize = 72;
+
+    /// Creates a view over the data of a market account, checking its discriminator and length
+    pub fn new(data: &'a [u8]) -> Result<Self, AccountError> {
+        check_account_data(data, &MARKET_DISCRIMINATOR, Market::ACCOUNT_LEN)?;
+
+        let token_mint0_offset =
+            Self::SWAP_AUTHORITY_OFFSET + get_option_len(data, Self::SWAP_AUTHORITY_OFFSET)?;
+        // `token_mint0`, `token_mint1`, `reserve0` and `reserve1`
+        let fee_reserve_offset = token_mint0_offset + 4 * 32;
+        let fee_reserve_last_update_offset =
+            fee_reserve_offset + get_option_len(data, fee_reserve_offset)?;
+
+        Ok(Self {
+            data,
+            token_mint0_offset,
+            fee_reserve_last_update_offset,
+        })
+    }
+
+    pub fn config(&self) -> Pubkey {
+        self.read_pubkey(Self::CONFIG_OFFSET)
+    }
+
+    pub fn creator(&self) -> Pubkey {
+        self.read_pubkey(Self::CREATOR_OFFSET)
+    }
+
+    pub fn swap_authority(&self) -> Option<Pubkey> {
+        self.read_option_pubkey(Self::SWAP_AUTHORITY_OFFSET)
+    }
+
+    pub fn token_mint0(&self) -> Pubkey {
+        self.read_pubkey(self.token_mint0_offset)
+    }
+
+    pub fn token_mint1(&self) -> Pubkey {
+        self.read_pubkey(self.token_mint0_offset + 32)
+    }
+
+    pub fn reserve0(&self) -> Pubkey {
+        self.read_pubkey(self.token_mint0_offset + 64)
+    }
+
+    pub fn reserve1(&self) -> Pubkey {
+        self.read_pubkey(self.token_mint0_offset + 96)
+    }
+
+    pub fn fee_reserve(&self) -> Option<Pubkey> {
+        self.read_option_pubkey(self.token_mint0_offset + 128)
+    }
+
+    pub fn fee_reserve_last_update(&self) -> i64 {
+        i64::from_le_bytes(self.read_array(self.fee_reserve_last_update_offset))
+    }
+
+    pub fn settings(&self) -> MarketSettings {
+        let offset = self.fee_reserve_last_update_offset + 8;
+
+        MarketSettings {
+            max_supply: u64::from_le_bytes(self.read_array(offset)),
+            sqrt_price_a_x96: u128::from_le_bytes(self.read_array(offset + 8)),
+            sqrt_price_b_x96: u128::from_le_bytes(self.read_array(offset + 24)),
+            liquidity_a: u128::from_le_bytes(self.read_array(offset + 40)),
+            liquidity_b: u128::from_le_bytes(self.read_array(offset + 56)),
+            fee: u32::from_le_bytes(self.read_array(offset + 72)),
+        }
+    }
+
+    pub fn sqrt_price_x96(&self) -> u128 {
+        // After `fee_reserve_last_update` and the 76 bytes of `settings`
+        u128::from_le_bytes(self.read_array(self.fee_reserve_last_update_offset + 84))
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.data[self.fee_reserve_last_update_offset + 100]
+    }
+
+    // In bounds, as the account length is checked and larger than the largest market layout
+    fn read_array<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data[offset..offset + N].try_into().unwrap()
+    }
+
+    fn read_pubkey(&self, offset: usize) -> Pubkey {
+        Pubkey::new_from_array(self.read_array(offset))
+    }
+
+    fn read_option_pubkey(&self, offset: usize) -> Option<Pubkey> {
+        (self.data[offset] == 1).then(|| self.read_pubkey(offset + 1))
+    }
+}
+
+// Length of a serialized `Option<Pubkey>`, depending on its tag
+fn get_option_len(data: &[u8], offset: usize) -> Result<usize, AccountError> {
+    match data[offset] {
+        0 => Ok(1),
+        1 => Ok(33),
+        tag => Err(AccountError::Deserialization(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid option tag {tag} at offset {offset}"),
+        ))),
+    }
+}
+
+fn check_owner(owner: &Pubkey) -> Result<(), AccountError> {
+    if *owner != TOKEN_MILL_V2_ID {
+        return Err(AccountError::InvalidOwner {
+            expected: TOKEN_MILL_V2_ID,
+            actual: *owner,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_account_data(
+    data: &[u8],
+    discriminator: &[u8; 8],
+    len: usize,
+) -> Result<(), AccountError> {
+    if !data.starts_with(discriminator) {
+        return Err(AccountError::InvalidDiscriminator {
+            expected: *discriminator,
+            actual: data.iter().take(8).copied().collect(),
+        });
+    }
+
+    if data.len() != len {
+        return Err(AccountError::InvalidLength {
+            expected: len,
+            actual: data.len(),
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_transaction_error::TransactionError;
+
+pub use super::generated::errors::*;
+use super::generated::programs::TOKEN_MILL_V2_ID;
+
+impl TokenMillV2Error {
+    /// Decodes the program error returned by an instruction of `program_id`
+    /// Returns `None` for other programs, as their custom error codes can overlap the `TokenMillV2Error` range
+    pub fn from_instruction_error(program_id: &Pubkey, error: &InstructionError) -> Option<Self> {
+        match error {
+            InstructionError::Custom(error_code) if *program_id == TOKEN_MILL_V2_ID => {
+                Self::from_u32(*error_code)
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the program error from a failed transaction, given the instructions it was built from
+    /// Only errors of top-level Token Mill instructions are decoded: a failing program calling Token Mill,
+    /// such as a swap aggregator, returns the error under its own instruction and can't be told apart
+    pub fn from_transaction_error(
+        error: &TransactionError,
+        instructions: &[Instruction],
+    ) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(index, error) => {
+                let instruction = instructions.get(usize::from(*index))?;
+
+                Self::from_instruction_error(&instruction.program_id, error)
+            }
+            _ => None,
+        }
+    }
+}
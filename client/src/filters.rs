@@ -0,0 +1,83 @@
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_program::pubkey::Pubkey;
+
+use super::accounts::{MARKET_DISCRIMINATOR, Market};
+
+// `Market` layout offsets, fields after `swap_authority` shift by 32 bytes when it is set
+pub const MARKET_CONFIG_OFFSET: usize = 8;
+pub const MARKET_CREATOR_OFFSET: usize = 40;
+pub const MARKET_SWAP_AUTHORITY_OFFSET: usize = 72;
+/// Offset of the `fee_reserve` option tag, when the market has no `swap_authority`
+/// After `swap_authority` come `token_mint0`, `token_mint1`, `reserve0` and `reserve1`
+pub const MARKET_FEE_RESERVE_OFFSET: usize = MARKET_SWAP_AUTHORITY_OFFSET + 1 + 4 * 32;
+
+const OPTION_NONE: u8 = 0;
+const OPTION_SOME: u8 = 1;
+
+/// Offset of `fee_reserve`, depending on whether `swap_authority` is set
+pub fn get_market_fee_reserve_offset(has_swap_authority: bool) -> usize {
+    if has_swap_authority {
+        MARKET_FEE_RESERVE_OFFSET + 32
+    } else {
+        MARKET_FEE_RESERVE_OFFSET
+    }
+}
+
+/// Filters matching all market accounts
+pub fn get_market_filters() -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(Market::ACCOUNT_LEN as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, MARKET_DISCRIMINATOR.to_vec())),
+    ]
+}
+
+pub fn get_markets_by_config_filters(config: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = get_market_filters();
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        MARKET_CONFIG_OFFSET,
+        config.to_bytes().to_vec(),
+    )));
+
+    filters
+}
+
+pub fn get_markets_by_creator_filters(creator: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = get_market_filters();
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        MARKET_CREATOR_OFFSET,
+        creator.to_bytes().to_vec(),
+    )));
+
+    filters
+}
+
+pub fn get_markets_with_swap_authority_filters() -> Vec<RpcFilterType> {
+    let mut filters = get_market_filters();
+    filters.push(get_option_filter(MARKET_SWAP_AUTHORITY_OFFSET, true));
+
+    filters
+}
+
+/// Filters matching markets with a `fee_reserve`, so opted out of KotM
+/// The `fee_reserve` offset depends on `swap_authority`, so one filter set is returned for each case, to be queried separately
+pub fn get_markets_opted_out_of_kotm_filters() -> [Vec<RpcFilterType>; 2] {
+    [false, true].map(|has_swap_authority| {
+        let mut filters = get_market_filters();
+        filters.push(get_option_filter(
+            MARKET_SWAP_AUTHORITY_OFFSET,
+            has_swap_authority,
+        ));
+        filters.push(get_option_filter(
+            get_market_fee_reserve_offset(has_swap_authority),
+            true,
+        ));
+
+        filters
+    })
+}
+
+fn get_option_filter(offset: usize, is_some: bool) -> RpcFilterType {
+    let tag = if is_some { OPTION_SOME } else { OPTION_NONE };
+
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, vec![tag]))
+}
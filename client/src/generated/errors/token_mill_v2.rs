@@ -10,71 +10,71 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum TokenMillV2Error {
-    /// 6000 -
-    #[error("")]
+    /// 6000 - Division by zero
+    #[error("Division by zero")]
     DivisionByZero = 0x1770,
-    /// 6001 -
-    #[error("")]
+    /// 6001 - Amount overflow
+    #[error("Amount overflow")]
     AmountOverflow = 0x1771,
-    /// 6002 -
-    #[error("")]
+    /// 6002 - Amount in overflows a u64
+    #[error("Amount in overflows a u64")]
     AmountInOverflow = 0x1772,
-    /// 6003 -
-    #[error("")]
+    /// 6003 - Amount out overflows a u64
+    #[error("Amount out overflows a u64")]
     AmountOutOverflow = 0x1773,
-    /// 6004 -
-    #[error("")]
+    /// 6004 - Liquidity overflow when computing the token 0 amount
+    #[error("Liquidity overflow when computing the token 0 amount")]
     LiquidityOverflow0 = 0x1774,
-    /// 6005 -
-    #[error("")]
+    /// 6005 - Liquidity overflow when computing the token 1 amount
+    #[error("Liquidity overflow when computing the token 1 amount")]
     LiquidityOverflow1 = 0x1775,
-    /// 6006 -
-    #[error("")]
+    /// 6006 - Sqrt price overflow
+    #[error("Sqrt price overflow")]
     PriceOverflow = 0x1776,
-    /// 6007 -
-    #[error("")]
+    /// 6007 - Fee amount overflows a u64
+    #[error("Fee amount overflows a u64")]
     FeeAmountOverflow = 0x1777,
-    /// 6008 -
-    #[error("")]
+    /// 6008 - The config admin must sign this instruction
+    #[error("The config admin must sign this instruction")]
     AdminSignatureRequired = 0x1778,
-    /// 6009 -
-    #[error("")]
+    /// 6009 - The market creator must sign this instruction
+    #[error("The market creator must sign this instruction")]
     CreatorSignatureRequired = 0x1779,
-    /// 6010 -
-    #[error("")]
+    /// 6010 - The market swap authority must sign swaps on this market
+    #[error("The market swap authority must sign swaps on this market")]
     AuthoritySignatureRequired = 0x177A,
-    /// 6011 -
-    #[error("")]
+    /// 6011 - Fee recipient update is on cooldown, retry once the config cooldown has elapsed
+    #[error("Fee recipient update is on cooldown, retry once the config cooldown has elapsed")]
     FeeRecipientUpdateOnCd = 0x177B,
-    /// 6012 -
-    #[error("")]
+    /// 6012 - The market swap authority has already been removed
+    #[error("The market swap authority has already been removed")]
     SwapAuthorityAlreadyRemoved = 0x177C,
-    /// 6013 -
-    #[error("")]
+    /// 6013 - Invalid fee reserve, expected the market fee reserve, or the config creator fee pool if the market has none
+    #[error("Invalid fee reserve, expected the market fee reserve, or the config creator fee pool if the market has none")]
     InvalidFeeReserve = 0x177D,
-    /// 6014 -
-    #[error("")]
+    /// 6014 - Fee must be lower than 1_000_000 (100%)
+    #[error("Fee must be lower than 1_000_000 (100%)")]
     InvalidFee = 0x177E,
-    /// 6015 -
-    #[error("")]
+    /// 6015 - Token mint 1 must be the config quote token mint
+    #[error("Token mint 1 must be the config quote token mint")]
     InvalidQuoteTokenMint = 0x177F,
-    /// 6016 -
-    #[error("")]
+    /// 6016 - Sqrt price limit must be past the current price in the swap direction, and within the curve bounds
+    #[error("Sqrt price limit must be past the current price in the swap direction, and within the curve bounds")]
     InvalidSqrtPriceLimit = 0x1780,
-    /// 6017 -
-    #[error("")]
+    /// 6017 - Sqrt price A must be lower than sqrt price B, and both within the curve bounds
+    #[error("Sqrt price A must be lower than sqrt price B, and both within the curve bounds")]
     InvalidSqrtPrices = 0x1781,
-    /// 6018 -
-    #[error("")]
+    /// 6018 - Swap amount must not be zero
+    #[error("Swap amount must not be zero")]
     ZeroDeltaAmount = 0x1782,
-    /// 6019 -
-    #[error("")]
+    /// 6019 - Slippage exceeded, requote the swap or increase the slippage tolerance
+    #[error("Slippage exceeded, requote the swap or increase the slippage tolerance")]
     SlippageExceeded = 0x1783,
-    /// 6020 -
-    #[error("")]
+    /// 6020 - Only opting in KotM is allowed, the new fee reserve must be None
+    #[error("Only opting in KotM is allowed, the new fee reserve must be None")]
     CanOnlyOptInKOTM = 0x1784,
-    /// 6021 -
-    #[error("")]
+    /// 6021 - Amount underflow
+    #[error("Amount underflow")]
     AmountUnderflow = 0x1785,
 }
 
@@ -1,20 +1,20 @@
 #![allow(unexpected_cfgs, unused_imports, dead_code, deprecated)]
 
+#[allow(clippy::io_other_error)]
 mod generated;
 
 use generated::*;
 
-pub mod accounts {
-    pub use super::generated::accounts::*;
-}
+pub mod accounts;
 
 pub mod instructions {
     pub use super::generated::instructions::*;
 }
 
-pub mod errors {
-    pub use super::generated::errors::*;
-}
+pub mod errors;
+
+#[cfg(feature = "fetch")]
+pub mod filters;
 
 pub mod shared {
     pub use super::generated::shared::*;
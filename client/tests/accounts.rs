@@ -0,0 +1,88 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use token_mill_v2_client::{
+    accounts::{AccountError, Market, MarketView, TokenMillConfig},
+    programs::TOKEN_MILL_V2_ID,
+};
+
+#[test]
+fn checked_market_deserialization() {
+    let market = get_market();
+
+    let mut data = borsh::to_vec(&market).unwrap();
+    data.resize(Market::ACCOUNT_LEN, 0);
+
+    assert_eq!(
+        Market::from_account(&TOKEN_MILL_V2_ID, &data).unwrap(),
+        market
+    );
+
+    assert!(matches!(
+        Market::from_account(&Pubkey::default(), &data),
+        Err(AccountError::InvalidOwner { .. })
+    ));
+    assert!(matches!(
+        Market::from_account_data(&data[..Market::ACCOUNT_LEN - 1]),
+        Err(AccountError::InvalidLength { .. })
+    ));
+    assert!(matches!(
+        TokenMillConfig::from_account_data(&data),
+        Err(AccountError::InvalidDiscriminator { .. })
+    ));
+
+    // Invalid `swap_authority` option tag
+    data[72] = 2;
+    assert!(matches!(
+        Market::from_account_data(&data),
+        Err(AccountError::Deserialization(_))
+    ));
+}
+
+#[test]
+fn market_view() {
+    for swap_authority in [None, Some(Pubkey::new_unique())] {
+        for fee_reserve in [None, Some(Pubkey::new_unique())] {
+            let market = Market {
+                swap_authority,
+                fee_reserve,
+                ..get_market()
+            };
+
+            let mut data = borsh::to_vec(&market).unwrap();
+            data.resize(Market::ACCOUNT_LEN, 0);
+
+            let view = MarketView::new(&data).unwrap();
+
+            assert_eq!(view.config(), market.config);
+            assert_eq!(view.creator(), market.creator);
+            assert_eq!(view.swap_authority(), market.swap_authority);
+            assert_eq!(view.token_mint0(), market.token_mint0);
+            assert_eq!(view.token_mint1(), market.token_mint1);
+            assert_eq!(view.reserve0(), market.reserve0);
+            assert_eq!(view.reserve1(), market.reserve1);
+            assert_eq!(view.fee_reserve(), market.fee_reserve);
+            assert_eq!(
+                view.fee_reserve_last_update(),
+                market.fee_reserve_last_update
+            );
+            assert_eq!(view.settings(), market.settings);
+            assert_eq!(view.sqrt_price_x96(), market.sqrt_price_x96);
+            assert_eq!(view.bump(), market.bump[0]);
+        }
+    }
+
+    let mut data = borsh::to_vec(&get_market()).unwrap();
+    data.resize(Market::ACCOUNT_LEN, 0);
+    data[72] = 2;
+
+    assert!(matches!(
+        MarketView::new(&data),
+        Err(AccountError::Deserialization(_))
+    ));
+    assert!(matches!(
+        MarketView::new(&data[..100]),
+        Err(AccountError::InvalidLength { .. })
+    ));
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "anchor")]
+
+mod common;
+
+use anchor_lang::{AccountDeserialize, Discriminator, Owner};
+use common::*;
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market, TOKEN_MILL_CONFIG_DISCRIMINATOR, TokenMillConfig},
+    programs::TOKEN_MILL_V2_ID,
+};
+
+#[test]
+fn anchor_account() {
+    let market = get_market();
+    let data = borsh::to_vec(&market).unwrap();
+
+    let deserialized = Market::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+
+    assert_eq!(deserialized, market);
+    assert_eq!(Market::owner(), TOKEN_MILL_V2_ID);
+}
+
+#[test]
+fn anchor_discriminator() {
+    assert_eq!(Market::DISCRIMINATOR, MARKET_DISCRIMINATOR);
+    assert_eq!(
+        TokenMillConfig::DISCRIMINATOR,
+        TOKEN_MILL_CONFIG_DISCRIMINATOR
+    );
+}
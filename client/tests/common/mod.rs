@@ -0,0 +1,37 @@
+use solana_program::{pubkey, pubkey::Pubkey};
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market},
+    types::MarketSettings,
+};
+
+pub const CONFIG: Pubkey = pubkey!("D6qYhV5juiHXsqCa72iaKJWs2nAexJPnFA59764rAu93");
+pub const CREATOR: Pubkey = pubkey!("7ZJRjoNm7DjokzybCnaMqJ9bNQWTPkW4BuackD6zr9PD");
+pub const TOKEN_MINT_0: Pubkey = pubkey!("CDCD3xq4DN3kXx18Tdpx4FH4zFZ2ciCaGqDEErmLSSfu");
+pub const TOKEN_MINT_1: Pubkey = pubkey!("G1RFVzDxJRSeYHE1KXExLVw1Avo7kaKwtLjLXLfpiXvg");
+
+pub fn get_market() -> Market {
+    let (market, bump) = Market::find_pda(&TOKEN_MINT_0);
+
+    Market {
+        discriminator: MARKET_DISCRIMINATOR,
+        config: CONFIG,
+        creator: CREATOR,
+        swap_authority: None,
+        token_mint0: TOKEN_MINT_0,
+        token_mint1: TOKEN_MINT_1,
+        reserve0: Pubkey::new_unique(),
+        reserve1: Pubkey::new_unique(),
+        fee_reserve: Some(market),
+        fee_reserve_last_update: 200,
+        settings: MarketSettings {
+            max_supply: 1_000_000_000_000_000,
+            sqrt_price_a_x96: 419236029690706642379639606,
+            sqrt_price_b_x96: 1544441212687274377713657485,
+            liquidity_a: 1 << 100,
+            liquidity_b: 1 << 90,
+            fee: 10_000,
+        },
+        sqrt_price_x96: 419236029690706642379639606,
+        bump: [bump],
+    }
+}
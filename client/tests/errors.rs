@@ -0,0 +1,57 @@
+use num_traits::FromPrimitive;
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_transaction_error::TransactionError;
+use token_mill_v2_client::{errors::TokenMillV2Error, programs::TOKEN_MILL_V2_ID};
+
+#[test]
+fn transaction_error_decoding() {
+    let instructions = [
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]),
+        Instruction::new_with_bytes(TOKEN_MILL_V2_ID, &[], vec![]),
+    ];
+    let slippage_error =
+        |index| TransactionError::InstructionError(index, InstructionError::Custom(0x1783));
+
+    assert_eq!(
+        TokenMillV2Error::from_transaction_error(&slippage_error(1), &instructions),
+        Some(TokenMillV2Error::SlippageExceeded)
+    );
+
+    // Same error code returned by another program
+    assert_eq!(
+        TokenMillV2Error::from_transaction_error(&slippage_error(0), &instructions),
+        None
+    );
+    // Instruction missing from the list
+    assert_eq!(
+        TokenMillV2Error::from_transaction_error(&slippage_error(2), &instructions),
+        None
+    );
+
+    for error in [
+        TransactionError::InstructionError(1, InstructionError::Custom(1)),
+        TransactionError::InstructionError(1, InstructionError::InvalidAccountData),
+        TransactionError::AccountNotFound,
+    ] {
+        assert_eq!(
+            TokenMillV2Error::from_transaction_error(&error, &instructions),
+            None
+        );
+    }
+}
+
+#[test]
+fn error_messages() {
+    for error_code in 0x1770..=0x1785 {
+        let error = TokenMillV2Error::from_u32(error_code).unwrap();
+        assert!(!error.to_string().is_empty(), "{error:?}");
+    }
+
+    assert_eq!(
+        TokenMillV2Error::SlippageExceeded.to_string(),
+        "Slippage exceeded, requote the swap or increase the slippage tolerance"
+    );
+}
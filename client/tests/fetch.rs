@@ -0,0 +1,67 @@
+#![cfg(feature = "fetch")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::*;
+use serde_json::{Value, json};
+use solana_account_decoder::{UiAccountEncoding, encode_ui_account};
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use token_mill_v2_client::{
+    accounts::{Market, fetch_market, fetch_maybe_market},
+    programs::TOKEN_MILL_V2_ID,
+    shared::MaybeAccount,
+};
+
+fn get_rpc_client(account: Option<(&Pubkey, &Account)>) -> RpcClient {
+    let value = account.map_or(Value::Null, |(address, account)| {
+        json!(encode_ui_account(
+            address,
+            account,
+            UiAccountEncoding::Base64,
+            None,
+            None
+        ))
+    });
+
+    let mocks = HashMap::from([(
+        RpcRequest::GetMultipleAccounts,
+        json!({
+            "context": { "slot": 1 },
+            "value": [value],
+        }),
+    )]);
+
+    RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+}
+
+#[test]
+fn fetch() {
+    let market = get_market();
+    let address = Market::find_pda(&TOKEN_MINT_0).0;
+    let account = Account {
+        lamports: 1_000_000,
+        data: borsh::to_vec(&market).unwrap(),
+        owner: TOKEN_MILL_V2_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let rpc_client = get_rpc_client(Some((&address, &account)));
+    let decoded_account = fetch_market(&rpc_client, &address).unwrap();
+
+    assert_eq!(decoded_account.address, address);
+    assert_eq!(decoded_account.account, account);
+    assert_eq!(decoded_account.data, market);
+
+    let rpc_client = get_rpc_client(None);
+    assert!(fetch_market(&rpc_client, &address).is_err());
+
+    let rpc_client = get_rpc_client(None);
+    assert!(matches!(
+        fetch_maybe_market(&rpc_client, &address).unwrap(),
+        MaybeAccount::NotFound(not_found) if not_found == address
+    ));
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "fetch")]
+
+mod common;
+
+use common::*;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_program::pubkey::Pubkey;
+use token_mill_v2_client::{accounts::Market, filters::*};
+
+fn get_data(market: &Market) -> Vec<u8> {
+    let mut data = borsh::to_vec(market).unwrap();
+    data.resize(Market::ACCOUNT_LEN, 0);
+
+    data
+}
+
+// Same matching as the RPC node
+fn allows_data(filters: &[RpcFilterType], data: &[u8]) -> bool {
+    filters.iter().all(|filter| match filter {
+        RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+        _ => unreachable!(),
+    })
+}
+
+fn allows(filters: &[RpcFilterType], market: &Market) -> bool {
+    allows_data(filters, &get_data(market))
+}
+
+#[test]
+fn market_filters() {
+    let swap_authority = Pubkey::new_unique();
+    let fee_reserve = Pubkey::new_unique();
+
+    let mut markets = Vec::new();
+    for swap_authority in [None, Some(swap_authority)] {
+        for fee_reserve in [None, Some(fee_reserve)] {
+            markets.push(Market {
+                swap_authority,
+                fee_reserve,
+                ..get_market()
+            });
+        }
+    }
+
+    let [opted_out_filters, opted_out_with_authority_filters] =
+        get_markets_opted_out_of_kotm_filters();
+
+    for market in &markets {
+        assert!(allows(&get_market_filters(), market));
+        assert!(allows(&get_markets_by_config_filters(&CONFIG), market));
+        assert!(!allows(&get_markets_by_config_filters(&CREATOR), market));
+        assert!(allows(&get_markets_by_creator_filters(&CREATOR), market));
+        assert!(!allows(&get_markets_by_creator_filters(&CONFIG), market));
+
+        assert_eq!(
+            allows(&get_markets_with_swap_authority_filters(), market),
+            market.swap_authority.is_some()
+        );
+        assert_eq!(
+            allows(&opted_out_filters, market) || allows(&opted_out_with_authority_filters, market),
+            market.fee_reserve.is_some()
+        );
+    }
+
+    // Other accounts are not matched
+    let mut data = get_data(&markets[0]);
+    data.pop();
+    assert!(!allows_data(&get_market_filters(), &data));
+
+    let mut data = get_data(&markets[0]);
+    data[0] = 0;
+    assert!(!allows_data(&get_market_filters(), &data));
+}
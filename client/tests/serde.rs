@@ -0,0 +1,31 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use token_mill_v2_client::{accounts::Market, types::SwapParameters};
+
+#[test]
+fn market_json_round_trip() {
+    let market = get_market();
+
+    // `serde_json::Value` doesn't support u128, so the JSON is checked as a string
+    let json = serde_json::to_string(&market).unwrap();
+
+    // Pubkeys are serialized as base58 strings
+    assert!(json.contains(&format!("\"config\":\"{CONFIG}\"")));
+    assert!(json.contains(&format!("\"token_mint0\":\"{TOKEN_MINT_0}\"")));
+
+    let deserialized: Market = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, market);
+}
+
+#[test]
+fn swap_parameters_json_round_trip() {
+    let swap_parameters = SwapParameters::BuyExactIn(1_000, 10);
+
+    let json = serde_json::to_string(&swap_parameters).unwrap();
+    let deserialized: SwapParameters = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(deserialized, swap_parameters);
+}
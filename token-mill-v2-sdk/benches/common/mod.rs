@@ -0,0 +1,59 @@
+// Each bench target only uses some of the helpers
+#![allow(dead_code)]
+
+use solana_sdk::pubkey::Pubkey;
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market},
+    types::MarketSettingsInput,
+};
+use token_mill_v2_sdk::{
+    quote::{MAX_SQRT_PRICE_X96, quote},
+    settings::get_market_settings,
+};
+
+pub const MAX_SUPPLY: u64 = 1_000_000_000_000_000; // 1e9 * 1e6
+pub const SUPPLY_AT_GRADUATION: u64 = 800_000_000_000_000; // 8e8 * 1e6
+pub const SQRT_PRICE_A: u128 = 419236029690706642379639606; // 2.8e-5
+pub const SQRT_PRICE_B: u128 = 1544441212687274377713657485; // 3.8e-4
+pub const FEE: u32 = 10_000; // 1%
+
+// 1e6 tokens, 1 token 1
+pub const AMOUNT_0: i64 = 1_000_000_000_000;
+pub const AMOUNT_1: i64 = 1_000_000_000;
+
+pub fn get_market() -> Market {
+    let settings = get_market_settings(&MarketSettingsInput {
+        max_supply: MAX_SUPPLY,
+        supply_at_graduation: SUPPLY_AT_GRADUATION,
+        sqrt_price_a_x96: SQRT_PRICE_A,
+        sqrt_price_b_x96: SQRT_PRICE_B,
+        fee: FEE,
+    })
+    .unwrap();
+
+    Market {
+        discriminator: MARKET_DISCRIMINATOR,
+        config: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        swap_authority: None,
+        token_mint0: Pubkey::new_unique(),
+        token_mint1: Pubkey::new_unique(),
+        reserve0: Pubkey::new_unique(),
+        reserve1: Pubkey::new_unique(),
+        fee_reserve: None,
+        fee_reserve_last_update: 0,
+        settings,
+        sqrt_price_x96: SQRT_PRICE_A,
+        bump: [255],
+    }
+}
+
+/// Market after buying `supply` tokens from the curve start
+pub fn get_market_at_supply(supply: u64) -> Market {
+    let mut market = get_market();
+
+    let result = quote(&market, false, -(supply as i64), MAX_SQRT_PRICE_X96).unwrap();
+    market.sqrt_price_x96 = result.next_sqrt_price;
+
+    market
+}
@@ -0,0 +1,111 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use jupiter_amm_interface::{
+    AccountMap, Amm, AmmContext, ClockRef, KeyedAccount, QuoteParams, SwapMode,
+};
+use solana_sdk::{account::Account, clock::Clock, pubkey::Pubkey};
+use token_mill_v2_client::{
+    accounts::{Market, TOKEN_MILL_CONFIG_DISCRIMINATOR, TokenMillConfig},
+    programs::TOKEN_MILL_V2_ID,
+};
+use token_mill_v2_sdk::jupiter::TokenMillV2Amm;
+
+use common::{AMOUNT_1, SUPPLY_AT_GRADUATION, get_market_at_supply};
+
+mod common;
+
+fn get_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_MILL_V2_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn get_market_account(market: &Market) -> Account {
+    let mut data = borsh::to_vec(market).unwrap();
+    data.resize(Market::ACCOUNT_LEN, 0);
+
+    get_account(data)
+}
+
+fn get_config_account(market: &Market) -> Account {
+    let config = TokenMillConfig {
+        discriminator: TOKEN_MILL_CONFIG_DISCRIMINATOR,
+        admin: Pubkey::new_unique(),
+        quote_token_mint: market.token_mint1,
+        protocol_fee_share: 400_000,
+        protocol_fee_reserve: Pubkey::new_unique(),
+        creator_fee_pool: Pubkey::new_unique(),
+        fee_recipient_change_cooldown: 3_600,
+        default_market_settings: market.settings.clone(),
+    };
+
+    let mut data = borsh::to_vec(&config).unwrap();
+    data.resize(TokenMillConfig::ACCOUNT_LEN, 0);
+
+    get_account(data)
+}
+
+fn jupiter(c: &mut Criterion) {
+    let market = get_market_at_supply(SUPPLY_AT_GRADUATION / 2);
+    let market_key = Pubkey::new_unique();
+    let market_account = get_market_account(&market);
+
+    let mut account_map: AccountMap = AccountMap::with_hasher(Default::default());
+    account_map.insert(market_key, market_account.clone());
+    account_map.insert(market.config, get_config_account(&market));
+
+    let mut amm = TokenMillV2Amm::from_keyed_account(
+        &KeyedAccount {
+            key: market_key,
+            account: market_account,
+            params: None,
+        },
+        &AmmContext {
+            clock_ref: ClockRef::from(Clock::default()),
+        },
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("jupiter");
+
+    // Deserializes the market and config accounts
+    group.bench_function("update", |b| {
+        b.iter(|| amm.update(black_box(&account_map)).unwrap())
+    });
+
+    for (swap, input_mint, output_mint, amount, swap_mode) in [
+        (
+            "buy_exact_in",
+            market.token_mint1,
+            market.token_mint0,
+            AMOUNT_1,
+            SwapMode::ExactIn,
+        ),
+        (
+            "sell_exact_out",
+            market.token_mint0,
+            market.token_mint1,
+            AMOUNT_1,
+            SwapMode::ExactOut,
+        ),
+    ] {
+        let quote_params = QuoteParams {
+            amount: amount as u64,
+            input_mint,
+            output_mint,
+            swap_mode,
+        };
+
+        group.bench_function(format!("quote/{swap}"), |b| {
+            b.iter(|| amm.quote(black_box(&quote_params)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, jupiter);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use token_mill_v2_sdk::{quote::quote, slippage::get_max_sqrt_price_limit};
+
+use common::{AMOUNT_0, AMOUNT_1, MAX_SUPPLY, SUPPLY_AT_GRADUATION, get_market_at_supply};
+
+mod common;
+
+fn quote_market(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quote");
+
+    // Markets in the middle of each pool
+    let pools = [
+        ("pool_a", get_market_at_supply(SUPPLY_AT_GRADUATION / 2)),
+        (
+            "pool_b",
+            get_market_at_supply(SUPPLY_AT_GRADUATION + (MAX_SUPPLY - SUPPLY_AT_GRADUATION) / 2),
+        ),
+    ];
+
+    for (name, market) in &pools {
+        for (swap, zero_for_one, delta_amount) in [
+            ("buy_exact_in", false, AMOUNT_1),
+            ("buy_exact_out", false, -AMOUNT_0),
+            ("sell_exact_in", true, AMOUNT_0),
+            ("sell_exact_out", true, -AMOUNT_1),
+        ] {
+            let sqrt_price_limit = get_max_sqrt_price_limit(market, zero_for_one);
+
+            group.bench_function(format!("{name}/{swap}"), |b| {
+                b.iter(|| {
+                    quote(
+                        black_box(market),
+                        zero_for_one,
+                        black_box(delta_amount),
+                        sqrt_price_limit,
+                    )
+                    .unwrap()
+                })
+            });
+        }
+    }
+
+    // Swaps crossing the boundary, 1e6 tokens away from it
+    let below_boundary = get_market_at_supply(SUPPLY_AT_GRADUATION - AMOUNT_0 as u64);
+    let above_boundary = get_market_at_supply(SUPPLY_AT_GRADUATION + AMOUNT_0 as u64);
+
+    for (swap, market, zero_for_one, delta_amount) in [
+        ("buy_exact_out", &below_boundary, false, -2 * AMOUNT_0),
+        ("sell_exact_in", &above_boundary, true, 2 * AMOUNT_0),
+    ] {
+        let sqrt_price_limit = get_max_sqrt_price_limit(market, zero_for_one);
+
+        // Same swaps in the other mode, reusing their amounts
+        let result = quote(market, zero_for_one, delta_amount, sqrt_price_limit).unwrap();
+        let other_swap = if delta_amount < 0 {
+            ("buy_exact_in", result.amount_in as i64)
+        } else {
+            ("sell_exact_out", -(result.amount_out as i64))
+        };
+        assert!(result.phase_crossed);
+
+        for (swap, delta_amount) in [(swap, delta_amount), other_swap] {
+            group.bench_function(format!("crossing/{swap}"), |b| {
+                b.iter(|| {
+                    quote(
+                        black_box(market),
+                        zero_for_one,
+                        black_box(delta_amount),
+                        sqrt_price_limit,
+                    )
+                    .unwrap()
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, quote_market);
+criterion_main!(benches);
@@ -0,0 +1,126 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_sdk::pubkey::Pubkey;
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market},
+    types::MarketSettingsInput,
+};
+use token_mill_v2_sdk::{
+    quote::{MAX_SQRT_PRICE_X96, quote},
+    settings::get_market_settings,
+};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    max_supply: u64,
+    supply_at_graduation: u64,
+    sqrt_price_a_x96: u128,
+    sqrt_price_b_x96: u128,
+    fee: u32,
+    sqrt_price_x96: u128,
+    zero_for_one: bool,
+    delta_amount: i64,
+    sqrt_price_limit: u128,
+}
+
+fuzz_target!(|input: Input| {
+    // Only settings the program accepts
+    let Ok(settings) = get_market_settings(&MarketSettingsInput {
+        max_supply: input.max_supply,
+        supply_at_graduation: input.supply_at_graduation,
+        sqrt_price_a_x96: input.sqrt_price_a_x96,
+        sqrt_price_b_x96: input.sqrt_price_b_x96,
+        fee: input.fee,
+    }) else {
+        return;
+    };
+
+    let sqrt_price_a_x96 = settings.sqrt_price_a_x96;
+    let sqrt_price_x96 =
+        sqrt_price_a_x96 + input.sqrt_price_x96 % (MAX_SQRT_PRICE_X96 - sqrt_price_a_x96 + 1);
+
+    let market = Market {
+        discriminator: MARKET_DISCRIMINATOR,
+        config: Pubkey::default(),
+        creator: Pubkey::default(),
+        swap_authority: None,
+        token_mint0: Pubkey::default(),
+        token_mint1: Pubkey::default(),
+        reserve0: Pubkey::default(),
+        reserve1: Pubkey::default(),
+        fee_reserve: None,
+        fee_reserve_last_update: 0,
+        settings,
+        sqrt_price_x96,
+        bump: [0],
+    };
+
+    // Limits the program accepts, past the current price and within the curve bounds
+    let zero_for_one = input.zero_for_one;
+    let sqrt_price_limit = if zero_for_one {
+        if sqrt_price_x96 == sqrt_price_a_x96 {
+            return;
+        }
+
+        sqrt_price_a_x96 + input.sqrt_price_limit % (sqrt_price_x96 - sqrt_price_a_x96)
+    } else {
+        if sqrt_price_x96 == MAX_SQRT_PRICE_X96 {
+            return;
+        }
+
+        sqrt_price_x96 + 1 + input.sqrt_price_limit % (MAX_SQRT_PRICE_X96 - sqrt_price_x96)
+    };
+
+    let delta_amount = input.delta_amount;
+    let Ok(result) = quote(&market, zero_for_one, delta_amount, sqrt_price_limit) else {
+        return;
+    };
+
+    assert!(result.amount_in >= result.fee_amount_token_in);
+
+    if delta_amount > 0 {
+        assert!(result.amount_in <= delta_amount.unsigned_abs());
+    } else {
+        assert!(result.amount_out <= delta_amount.unsigned_abs());
+    }
+
+    // Sells swap the fee after reaching the limit, down to at most the curve start
+    if zero_for_one {
+        assert!(sqrt_price_a_x96 <= result.next_sqrt_price);
+        assert!(result.next_sqrt_price <= sqrt_price_x96);
+    } else {
+        assert!(sqrt_price_x96 <= result.next_sqrt_price);
+        assert!(result.next_sqrt_price <= sqrt_price_limit);
+        assert_eq!(result.fee_amount_token_1, result.fee_amount_token_in);
+    }
+
+    // Getting the same amount out exactly never costs more, unless it moves the price further, as
+    // exact out swaps move to the pool or limit boundary when it's the whole amount up to it
+    if delta_amount <= 0 || result.amount_out == 0 {
+        return;
+    }
+
+    let Ok(amount_out) = i64::try_from(result.amount_out) else {
+        return;
+    };
+
+    if let Ok(exact_out_result) = quote(&market, zero_for_one, -amount_out, sqrt_price_limit) {
+        assert_eq!(exact_out_result.amount_out, result.amount_out);
+
+        // Sells swap the fee after the amount, which doesn't move the price on a deep enough pool, so
+        // sells ending at the same price, such as the curve start, can't be compared
+        let moved_further = if zero_for_one {
+            exact_out_result.next_sqrt_price <= result.next_sqrt_price
+        } else {
+            exact_out_result.next_sqrt_price > result.next_sqrt_price
+                || exact_out_result.next_sqrt_price == sqrt_price_limit
+        };
+
+        if !moved_further {
+            // Up to a unit of rounding of the fee added to the amount in
+            assert!(exact_out_result.amount_in <= result.amount_in + 1);
+        }
+    }
+});
@@ -0,0 +1,99 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use token_mill_v2_sdk::quote::{
+    MAX_SQRT_PRICE_X96,
+    swap_math::{
+        MAX_FEE_U128, get_delta_amounts, get_next_sqrt_ratio_from_amount_0,
+        get_next_sqrt_ratio_from_amount_1,
+    },
+};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    sqrt_price: u128,
+    target_sqrt_price: u128,
+    liquidity: u128,
+    delta_amount: i64,
+    fee: u32,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        sqrt_price,
+        target_sqrt_price,
+        liquidity,
+        delta_amount,
+        fee,
+    } = input;
+
+    // Prices and fees the program accepts
+    let sqrt_price = sqrt_price % MAX_SQRT_PRICE_X96 + 1;
+    let target_sqrt_price = target_sqrt_price % MAX_SQRT_PRICE_X96 + 1;
+    let fee = (u128::from(fee) % MAX_FEE_U128) as u32;
+
+    // Adding token 0 lowers the price, adding token 1 raises it
+    if let Ok(next_sqrt_price) =
+        get_next_sqrt_ratio_from_amount_0(sqrt_price, liquidity, delta_amount)
+    {
+        match delta_amount.signum() {
+            1 => assert!(next_sqrt_price <= sqrt_price),
+            -1 => assert!(next_sqrt_price >= sqrt_price),
+            _ => assert_eq!(next_sqrt_price, sqrt_price),
+        }
+    }
+    if let Ok(next_sqrt_price) =
+        get_next_sqrt_ratio_from_amount_1(sqrt_price, liquidity, delta_amount)
+    {
+        match delta_amount.signum() {
+            1 => assert!(next_sqrt_price >= sqrt_price),
+            -1 => assert!(next_sqrt_price <= sqrt_price),
+            _ => assert_eq!(next_sqrt_price, sqrt_price),
+        }
+    }
+
+    let Ok((next_sqrt_price, amount_in, amount_out, fee_amount)) =
+        get_delta_amounts(sqrt_price, target_sqrt_price, liquidity, delta_amount, fee)
+    else {
+        return;
+    };
+
+    // The price moves towards the target, without crossing it
+    if target_sqrt_price < sqrt_price {
+        assert!(target_sqrt_price <= next_sqrt_price && next_sqrt_price <= sqrt_price);
+    } else {
+        assert!(sqrt_price <= next_sqrt_price && next_sqrt_price <= target_sqrt_price);
+    }
+
+    if delta_amount <= 0 {
+        assert!(amount_out <= delta_amount.unsigned_abs());
+        return;
+    }
+
+    assert!(u128::from(amount_in) + u128::from(fee_amount) <= delta_amount.unsigned_abs().into());
+
+    // Getting the same amount out exactly never costs more, unless it is all the amount up to
+    // the target, as exact out swaps then move to the target rather than the rounded price
+    let Ok(amount_out) = i64::try_from(amount_out) else {
+        return;
+    };
+    if let Ok((
+        exact_out_next_sqrt_price,
+        exact_out_amount_in,
+        exact_out_amount_out,
+        exact_out_fee_amount,
+    )) = get_delta_amounts(sqrt_price, target_sqrt_price, liquidity, -amount_out, fee)
+    {
+        assert_eq!(exact_out_amount_out, amount_out as u64);
+
+        if exact_out_next_sqrt_price == target_sqrt_price {
+            return;
+        }
+
+        assert!(
+            u128::from(exact_out_amount_in) + u128::from(exact_out_fee_amount)
+                <= u128::from(amount_in) + u128::from(fee_amount)
+        );
+    }
+});
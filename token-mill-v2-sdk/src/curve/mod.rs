@@ -0,0 +1,224 @@
+use ruint::aliases::U256;
+
+use crate::{
+    error::{Result, SdkError},
+    pricing::get_price,
+    quote::{
+        MAX_SQRT_PRICE_X96,
+        math::mul_div_round_up,
+        swap_math::{SQRT_PRICE_SHIFT, get_amount_0, get_amount_1},
+    },
+};
+use token_mill_v2_client::{errors::TokenMillV2Error::AmountOverflow, types::MarketSettings};
+
+/// Point of the bonding curve, amounts are raw token amounts
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurvePoint {
+    /// Base token sold by the curve
+    pub supply: u64,
+    pub sqrt_price_x96: u128,
+    /// Price in quote token per base token, adjusted by the tokens decimals
+    pub price: f64,
+    /// Quote token needed to buy `supply` from the start of the curve, fees excluded
+    pub cumulative_quote: u128,
+}
+
+/// Samples `num_points` points evenly spaced in supply, from 0 to `max_supply`
+/// The pool A/B switch is always included, so the kink of the curve is preserved
+pub fn sample_curve(
+    settings: &MarketSettings,
+    num_points: usize,
+    decimals_0: u8,
+    decimals_1: u8,
+) -> Result<Vec<CurvePoint>> {
+    if num_points < 2 {
+        return Err(SdkError::InvalidInput(
+            "at least 2 points are required".to_string(),
+        ));
+    }
+
+    let supply_a = get_amount_0(
+        settings.sqrt_price_a_x96,
+        settings.sqrt_price_b_x96,
+        settings.liquidity_a,
+        false,
+    )?;
+    let supply_a = u64::try_from(supply_a).map_err(|_| AmountOverflow)?;
+
+    let mut supplies: Vec<u64> = (0..num_points)
+        .map(|i| (u128::from(settings.max_supply) * i as u128 / (num_points - 1) as u128) as u64)
+        .collect();
+
+    if let Err(index) = supplies.binary_search(&supply_a) {
+        supplies.insert(index, supply_a);
+    }
+
+    supplies
+        .into_iter()
+        .map(|supply| {
+            let sqrt_price_x96 = get_sqrt_price_at_supply(settings, supply, supply_a)?;
+
+            Ok(CurvePoint {
+                supply,
+                sqrt_price_x96,
+                price: get_price(sqrt_price_x96, decimals_0, decimals_1),
+                cumulative_quote: get_cumulative_quote(settings, sqrt_price_x96)?,
+            })
+        })
+        .collect()
+}
+
+/// Exports the points as CSV, with a header row
+pub fn to_csv(points: &[CurvePoint]) -> String {
+    let mut csv = String::from("supply,sqrt_price_x96,price,cumulative_quote\n");
+
+    for point in points {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            point.supply, point.sqrt_price_x96, point.price, point.cumulative_quote
+        ));
+    }
+
+    csv
+}
+
+/// Exports the points as a JSON array
+/// `u128` values are written as strings, as most JSON parsers can't represent them
+pub fn to_json(points: &[CurvePoint]) -> String {
+    let points: Vec<serde_json::Value> = points
+        .iter()
+        .map(|point| {
+            serde_json::json!({
+                "supply": point.supply,
+                "sqrt_price_x96": point.sqrt_price_x96.to_string(),
+                "price": point.price,
+                "cumulative_quote": point.cumulative_quote.to_string(),
+            })
+        })
+        .collect();
+
+    serde_json::Value::Array(points).to_string()
+}
+
+// Price after `supply` base tokens are bought from the start of the curve
+// Pool B sells its whole supply at `MAX_SQRT_PRICE_X96`, so the price is capped there
+fn get_sqrt_price_at_supply(settings: &MarketSettings, supply: u64, supply_a: u64) -> Result<u128> {
+    let (sqrt_price, liquidity, amount_0) = if supply < supply_a {
+        (settings.sqrt_price_a_x96, settings.liquidity_a, supply)
+    } else {
+        (
+            settings.sqrt_price_b_x96,
+            settings.liquidity_b,
+            supply - supply_a,
+        )
+    };
+
+    if amount_0 == 0 {
+        return Ok(sqrt_price);
+    }
+
+    let liquidity = U256::from(liquidity).saturating_shl(SQRT_PRICE_SHIFT);
+    let amount_0_x_price = U256::from(amount_0) * U256::from(sqrt_price);
+
+    if amount_0_x_price >= liquidity {
+        return Ok(MAX_SQRT_PRICE_X96);
+    }
+
+    Ok(mul_div_round_up(
+        liquidity,
+        U256::from(sqrt_price),
+        liquidity - amount_0_x_price,
+    )
+    .map_or(MAX_SQRT_PRICE_X96, |sqrt_price| {
+        sqrt_price.min(MAX_SQRT_PRICE_X96)
+    }))
+}
+
+fn get_cumulative_quote(settings: &MarketSettings, sqrt_price_x96: u128) -> Result<u128> {
+    let quote_a = get_amount_1(
+        settings.sqrt_price_a_x96,
+        sqrt_price_x96.min(settings.sqrt_price_b_x96),
+        settings.liquidity_a,
+        true,
+    )?;
+
+    let quote_b = if sqrt_price_x96 > settings.sqrt_price_b_x96 {
+        get_amount_1(
+            settings.sqrt_price_b_x96,
+            sqrt_price_x96,
+            settings.liquidity_b,
+            true,
+        )?
+    } else {
+        0
+    };
+
+    quote_a.checked_add(quote_b).ok_or(AmountOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        quote::quote,
+        test_utils::{
+            constants::{MARKET, MAX_SUPPLY, SQRT_PRICE_A, SQRT_PRICE_B},
+            instructions::get_vm_and_create_market,
+        },
+    };
+    use token_mill_v2_client::accounts::Market;
+
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let vm = get_vm_and_create_market();
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        let points = sample_curve(&market.settings, 11, 6, 9).unwrap();
+
+        // 11 points and the pool A/B switch
+        assert_eq!(points.len(), 12);
+        assert_eq!(points[0].sqrt_price_x96, SQRT_PRICE_A);
+        assert_eq!(points[0].cumulative_quote, 0);
+        assert_eq!(points[11].supply, MAX_SUPPLY);
+        assert_eq!(points[11].sqrt_price_x96, MAX_SQRT_PRICE_X96);
+        assert!(
+            points
+                .iter()
+                .any(|point| point.sqrt_price_x96 == SQRT_PRICE_B)
+        );
+
+        for window in points.windows(2) {
+            assert!(window[0].supply < window[1].supply);
+            assert!(window[0].sqrt_price_x96 < window[1].sqrt_price_x96);
+            assert!(window[0].cumulative_quote < window[1].cumulative_quote);
+        }
+
+        // Buying the supply of a point moves the price to it
+        let point = &points[5];
+        let result = quote(
+            &market,
+            false,
+            -i64::try_from(point.supply).unwrap(),
+            MAX_SQRT_PRICE_X96,
+        )
+        .unwrap();
+
+        assert_eq!(result.next_sqrt_price, point.sqrt_price_x96);
+        assert_eq!(
+            u128::from(result.amount_in - result.fee_amount_token_in),
+            point.cumulative_quote
+        );
+
+        let csv = to_csv(&points);
+        assert_eq!(csv.lines().count(), 13);
+        assert!(csv.starts_with("supply,sqrt_price_x96,price,cumulative_quote\n0,"));
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&points)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 12);
+        assert_eq!(
+            json[11]["sqrt_price_x96"],
+            MAX_SQRT_PRICE_X96.to_string().as_str()
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use solana_sdk::pubkey::{ParsePubkeyError, PubkeyError};
+use thiserror::Error;
+use token_mill_v2_client::{accounts::AccountError, errors::TokenMillV2Error};
+
+pub type Result<T, E = SdkError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum SdkError {
+    /// Errors also returned by the program, when the SDK mirrors its checks
+    #[error("token mill error: {0}")]
+    Program(#[from] TokenMillV2Error),
+    /// Account that isn't a Token Mill V2 account of the expected type
+    #[error("invalid account: {0}")]
+    InvalidAccount(#[from] AccountError),
+    /// Account data that can't be deserialized
+    #[error("deserialization error: {0}")]
+    Deserialization(#[from] std::io::Error),
+    /// Integer conversion not covered by a program error
+    #[error("conversion error: {0}")]
+    Conversion(#[from] std::num::TryFromIntError),
+    #[error("invalid pubkey: {0}")]
+    InvalidPubkey(#[from] ParsePubkeyError),
+    #[error("invalid program address: {0}")]
+    InvalidProgramAddress(#[from] PubkeyError),
+    #[cfg(feature = "vanity")]
+    #[error("invalid base58: {0}")]
+    InvalidBase58(#[from] bs58::decode::Error),
+    #[error("invalid base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[cfg(feature = "vanity")]
+    #[error("transaction serialization error: {0}")]
+    TransactionSerialization(#[from] bincode::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "vanity")]
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Unexpected response from the vanity service
+    #[cfg(feature = "vanity")]
+    #[error("vanity service error: {0}")]
+    Vanity(String),
+    /// Instruction that isn't a valid Token Mill V2 instruction
+    #[error("invalid instruction: {0}")]
+    InvalidInstruction(String),
+    /// Arguments rejected before reaching the program
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+}
+
+impl SdkError {
+    pub fn program_error(&self) -> Option<&TokenMillV2Error> {
+        match self {
+            SdkError::Program(error) => Some(error),
+            _ => None,
+        }
+    }
+}
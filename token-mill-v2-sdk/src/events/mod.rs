@@ -0,0 +1,268 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use borsh::BorshDeserialize;
+use solana_sdk::{message::inner_instruction::InnerInstructionsList, pubkey::Pubkey};
+
+use crate::error::Result;
+use token_mill_v2_client::{
+    programs::TOKEN_MILL_V2_ID,
+    types::{
+        ConfigCreation, ConfigDefaultMarketSettingsUpdate, ConfigFeeSettingsUpdate,
+        ConfigOwnershipTransfer, FeeReserveUpdate, MarketCreation, MarketSwapAuthorityRemoved,
+        Swap,
+    },
+};
+
+/// Prefix of the self CPI instruction data used by the program to emit events
+pub const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+// First 8 bytes of `sha256("event:<EventName>")`
+pub const CONFIG_CREATION_DISCRIMINATOR: [u8; 8] = [22, 226, 190, 234, 176, 24, 250, 11];
+pub const CONFIG_DEFAULT_MARKET_SETTINGS_UPDATE_DISCRIMINATOR: [u8; 8] =
+    [86, 122, 72, 223, 93, 152, 224, 97];
+pub const CONFIG_FEE_SETTINGS_UPDATE_DISCRIMINATOR: [u8; 8] = [137, 142, 221, 54, 111, 184, 135, 2];
+pub const CONFIG_OWNERSHIP_TRANSFER_DISCRIMINATOR: [u8; 8] = [244, 36, 50, 39, 145, 158, 100, 124];
+pub const FEE_RESERVE_UPDATE_DISCRIMINATOR: [u8; 8] = [114, 245, 136, 81, 246, 204, 133, 12];
+pub const MARKET_CREATION_DISCRIMINATOR: [u8; 8] = [77, 174, 149, 37, 77, 226, 133, 219];
+pub const MARKET_SWAP_AUTHORITY_REMOVED_DISCRIMINATOR: [u8; 8] =
+    [61, 11, 41, 211, 46, 134, 227, 249];
+pub const SWAP_DISCRIMINATOR: [u8; 8] = [81, 108, 227, 190, 205, 208, 10, 196];
+
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenMillEvent {
+    ConfigCreation(ConfigCreation),
+    ConfigDefaultMarketSettingsUpdate(ConfigDefaultMarketSettingsUpdate),
+    ConfigFeeSettingsUpdate(ConfigFeeSettingsUpdate),
+    ConfigOwnershipTransfer(ConfigOwnershipTransfer),
+    FeeReserveUpdate(FeeReserveUpdate),
+    MarketCreation(MarketCreation),
+    MarketSwapAuthorityRemoved(MarketSwapAuthorityRemoved),
+    Swap(Swap),
+}
+
+impl TokenMillEvent {
+    /// Decodes an event from its discriminator followed by its borsh data
+    /// Returns `None` if the discriminator doesn't match any event
+    pub fn from_bytes(data: &[u8]) -> Result<Option<Self>> {
+        let Some((discriminator, mut data)) = data.split_first_chunk::<8>() else {
+            return Ok(None);
+        };
+
+        let event = match *discriminator {
+            CONFIG_CREATION_DISCRIMINATOR => {
+                Self::ConfigCreation(ConfigCreation::deserialize(&mut data)?)
+            }
+            CONFIG_DEFAULT_MARKET_SETTINGS_UPDATE_DISCRIMINATOR => {
+                Self::ConfigDefaultMarketSettingsUpdate(
+                    ConfigDefaultMarketSettingsUpdate::deserialize(&mut data)?,
+                )
+            }
+            CONFIG_FEE_SETTINGS_UPDATE_DISCRIMINATOR => {
+                Self::ConfigFeeSettingsUpdate(ConfigFeeSettingsUpdate::deserialize(&mut data)?)
+            }
+            CONFIG_OWNERSHIP_TRANSFER_DISCRIMINATOR => {
+                Self::ConfigOwnershipTransfer(ConfigOwnershipTransfer::deserialize(&mut data)?)
+            }
+            FEE_RESERVE_UPDATE_DISCRIMINATOR => {
+                Self::FeeReserveUpdate(FeeReserveUpdate::deserialize(&mut data)?)
+            }
+            MARKET_CREATION_DISCRIMINATOR => {
+                Self::MarketCreation(MarketCreation::deserialize(&mut data)?)
+            }
+            MARKET_SWAP_AUTHORITY_REMOVED_DISCRIMINATOR => Self::MarketSwapAuthorityRemoved(
+                MarketSwapAuthorityRemoved::deserialize(&mut data)?,
+            ),
+            SWAP_DISCRIMINATOR => Self::Swap(Swap::deserialize(&mut data)?),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+
+    /// Decodes an event from the data of a self CPI instruction
+    /// Returns `None` if the data isn't an event emitted by CPI
+    pub fn from_instruction_data(data: &[u8]) -> Result<Option<Self>> {
+        match data.strip_prefix(&EVENT_IX_TAG) {
+            Some(data) => Self::from_bytes(data),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Events emitted by CPI in a transaction, in execution order
+/// `account_keys` are the transaction account keys, used to only keep instructions to the Token Mill program
+pub fn parse_events_from_inner_instructions(
+    account_keys: &[Pubkey],
+    inner_instructions: &InnerInstructionsList,
+) -> Result<Vec<TokenMillEvent>> {
+    let mut events = Vec::new();
+
+    for inner_instruction in inner_instructions.iter().flatten() {
+        let instruction = &inner_instruction.instruction;
+
+        if account_keys.get(usize::from(instruction.program_id_index)) != Some(&TOKEN_MILL_V2_ID) {
+            continue;
+        }
+
+        if let Some(event) = TokenMillEvent::from_instruction_data(&instruction.data)? {
+            events.push(event);
+        }
+    }
+
+    Ok(events)
+}
+
+/// Events logged as "Program data:" in a transaction logs, in execution order
+/// The invocation stack is tracked from the logs so that data logged by other programs is ignored
+pub fn parse_events_from_logs(logs: &[String]) -> Result<Vec<TokenMillEvent>> {
+    let mut events = Vec::new();
+    let mut program_stack = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+            if program_stack.last() != Some(&TOKEN_MILL_V2_ID) {
+                continue;
+            }
+
+            let mut bytes = Vec::new();
+            for field in data.split_whitespace() {
+                bytes.extend(BASE64_STANDARD.decode(field)?);
+            }
+
+            if let Some(event) = TokenMillEvent::from_bytes(&bytes)? {
+                events.push(event);
+            }
+        } else if let Some((program_id, status)) = log
+            .strip_prefix("Program ")
+            .and_then(|log| log.split_once(' '))
+        {
+            let Ok(program_id) = program_id.parse::<Pubkey>() else {
+                continue;
+            };
+
+            if status.starts_with("invoke [") {
+                program_stack.push(program_id);
+            } else if status == "success" || status.starts_with("failed") {
+                program_stack.pop();
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use solana_sdk::message::Message;
+    use token_mill_v2_client::types::{SwapParameters, SwapResult};
+
+    use crate::test_utils::{
+        constants::{ALICE, BOB, CONFIG, MARKET, TOKEN_MINT_0, TOKEN_MINT_1},
+        instructions::{get_create_config_ix_builder, get_market_creation_ix, get_swap_ix_builder},
+        test_vm::{create_atas, create_tokens, execute_instructions, get_vm},
+    };
+
+    use super::*;
+
+    #[test]
+    fn events_from_inner_instructions() {
+        let mut vm = get_vm(vec![ALICE, BOB]);
+        create_tokens(&mut vm, [TOKEN_MINT_1], vec![ALICE, BOB], vec![], None);
+
+        let instructions = vec![
+            get_create_config_ix_builder().instruction(),
+            get_market_creation_ix(),
+        ];
+        let account_keys = Message::new(&instructions, Some(&ALICE)).account_keys;
+        let result = execute_instructions(&mut vm, instructions, &ALICE).unwrap();
+
+        let events =
+            parse_events_from_inner_instructions(&account_keys, &result.inner_instructions)
+                .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert!(
+            matches!(&events[0], TokenMillEvent::ConfigCreation(event) if event.config == CONFIG)
+        );
+        assert!(matches!(
+            &events[1],
+            TokenMillEvent::MarketCreation(event)
+                if event.market == MARKET && event.token_mint0 == TOKEN_MINT_0
+        ));
+
+        create_atas(&mut vm, vec![TOKEN_MINT_0], vec![ALICE]);
+
+        let mut swap_builder = get_swap_ix_builder();
+        swap_builder.swap_parameters(SwapParameters::BuyExactIn(1_000_000_000, 0));
+
+        let instructions = vec![swap_builder.instruction()];
+        let account_keys = Message::new(&instructions, Some(&ALICE)).account_keys;
+        let result = execute_instructions(&mut vm, instructions, &ALICE).unwrap();
+
+        let events =
+            parse_events_from_inner_instructions(&account_keys, &result.inner_instructions)
+                .unwrap();
+        let swap_result = SwapResult::try_from_slice(&result.return_data.data).unwrap();
+
+        assert_eq!(
+            events,
+            vec![TokenMillEvent::Swap(Swap {
+                config: CONFIG,
+                user: ALICE,
+                market: MARKET,
+                zero_for_one: false,
+                swap_result,
+            })]
+        );
+
+        // Instructions to other programs are ignored
+        assert!(
+            parse_events_from_inner_instructions(&[], &result.inner_instructions)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn events_from_logs() {
+        let swap = Swap {
+            config: CONFIG,
+            user: ALICE,
+            market: MARKET,
+            zero_for_one: true,
+            swap_result: SwapResult {
+                amount_in: 1,
+                amount_out: 2,
+                fee_amount_token_in: 3,
+                fee_amount_token1: 4,
+                next_sqrt_price: 5,
+            },
+        };
+
+        let mut data = SWAP_DISCRIMINATOR.to_vec();
+        swap.serialize(&mut data).unwrap();
+        let data_log = format!("{PROGRAM_DATA_LOG_PREFIX}{}", BASE64_STANDARD.encode(&data));
+
+        let other_program = BOB;
+        let logs = [
+            format!("Program {TOKEN_MILL_V2_ID} invoke [1]"),
+            data_log.clone(),
+            format!("Program {other_program} invoke [2]"),
+            data_log.clone(),
+            format!("Program {other_program} success"),
+            "Program log: Program data: spoofed".to_string(),
+            data_log,
+            format!("Program {TOKEN_MILL_V2_ID} success"),
+        ];
+
+        assert_eq!(
+            parse_events_from_logs(&logs).unwrap(),
+            vec![
+                TokenMillEvent::Swap(swap.clone()),
+                TokenMillEvent::Swap(swap)
+            ]
+        );
+    }
+}
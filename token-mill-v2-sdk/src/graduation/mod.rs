@@ -0,0 +1,176 @@
+use crate::{
+    error::Result,
+    quote::{quote_to_price, swap_math::get_amount_0},
+};
+use token_mill_v2_client::{accounts::Market, errors::TokenMillV2Error::AmountOverflow};
+
+/// Amount of base token sold by the curve, as implied by the current price
+pub fn get_circulating_supply(market: &Market) -> Result<u64> {
+    let settings = &market.settings;
+
+    let supply_a = get_amount_0(
+        settings.sqrt_price_a_x96,
+        market.sqrt_price_x96.min(settings.sqrt_price_b_x96),
+        settings.liquidity_a,
+        false,
+    )?;
+
+    let supply_b = if market.sqrt_price_x96 > settings.sqrt_price_b_x96 {
+        get_amount_0(
+            settings.sqrt_price_b_x96,
+            market.sqrt_price_x96,
+            settings.liquidity_b,
+            false,
+        )?
+    } else {
+        0
+    };
+
+    u64::try_from(supply_a + supply_b).map_err(|_| AmountOverflow.into())
+}
+
+/// Amount of base token sold by pool A, before switching to pool B
+pub fn get_supply_at_graduation(market: &Market) -> Result<u64> {
+    let settings = &market.settings;
+
+    get_amount_0(
+        settings.sqrt_price_a_x96,
+        settings.sqrt_price_b_x96,
+        settings.liquidity_a,
+        false,
+    )?
+    .try_into()
+    .map_err(|_| AmountOverflow.into())
+}
+
+/// Amount of base token left in pool A
+pub fn get_remaining_supply_before_graduation(market: &Market) -> Result<u64> {
+    if is_graduated(market) {
+        return Ok(0);
+    }
+
+    get_amount_0(
+        market.sqrt_price_x96,
+        market.settings.sqrt_price_b_x96,
+        market.settings.liquidity_a,
+        false,
+    )?
+    .try_into()
+    .map_err(|_| AmountOverflow.into())
+}
+
+/// Percentage of the pool A supply that has been sold, between 0 and 100
+pub fn get_graduation_progress(market: &Market) -> Result<f64> {
+    if is_graduated(market) {
+        return Ok(100.0);
+    }
+
+    // Markets graduating at 0% have nothing to sell in pool A
+    let supply_at_graduation = get_supply_at_graduation(market)?;
+    if supply_at_graduation == 0 {
+        return Ok(100.0);
+    }
+
+    let remaining_supply = get_remaining_supply_before_graduation(market)?;
+
+    Ok(
+        (supply_at_graduation - remaining_supply.min(supply_at_graduation)) as f64 * 100.0
+            / supply_at_graduation as f64,
+    )
+}
+
+/// Amount of quote token, fees included, to buy to reach the pool B price
+pub fn get_quote_amount_to_graduation(market: &Market) -> Result<u64> {
+    if is_graduated(market) {
+        return Ok(0);
+    }
+
+    Ok(quote_to_price(market, market.settings.sqrt_price_b_x96)?.amount_in)
+}
+
+pub fn is_graduated(market: &Market) -> bool {
+    market.sqrt_price_x96 >= market.settings.sqrt_price_b_x96
+}
+
+#[cfg(test)]
+mod tests {
+    use token_mill_v2_client::types::{MarketSettingsInput, SwapParameters};
+
+    use crate::{
+        settings::get_market_settings,
+        test_utils::{
+            constants::{
+                ALICE, FEE, MARKET, MAX_SUPPLY, SQRT_PRICE_A, SQRT_PRICE_B, SUPPLY_AT_GRADUATION,
+                TOKEN_MINT_0,
+            },
+            instructions::{get_swap_ix_builder, get_vm_and_create_market},
+            test_vm::{execute_instructions, get_token_balance},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn graduation() {
+        let mut vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        assert_eq!(get_circulating_supply(&market).unwrap(), 0);
+        assert_eq!(get_graduation_progress(&market).unwrap(), 0.0);
+        // Liquidity A is rounded down when the market settings are created
+        assert!(SUPPLY_AT_GRADUATION - get_supply_at_graduation(&market).unwrap() < 100);
+
+        let amount_to_graduation = get_quote_amount_to_graduation(&market).unwrap();
+
+        // Graduate in two swaps, checking the supply along the way
+        for amount_in in [
+            amount_to_graduation / 2,
+            amount_to_graduation - amount_to_graduation / 2,
+        ] {
+            let mut swap_builder = get_swap_ix_builder();
+            swap_builder.swap_parameters(SwapParameters::BuyExactIn(amount_in, 0));
+
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+            vm.expire_blockhash();
+
+            let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+            let reserve_0 = get_token_balance(&vm, &MARKET, &TOKEN_MINT_0);
+            let circulating_supply = get_circulating_supply(&market).unwrap();
+
+            assert_eq!(circulating_supply, MAX_SUPPLY - reserve_0);
+
+            let remaining_supply = get_remaining_supply_before_graduation(&market).unwrap();
+            let supply_at_graduation = get_supply_at_graduation(&market).unwrap();
+            assert!(supply_at_graduation.abs_diff(remaining_supply + circulating_supply) <= 1);
+        }
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        assert!(is_graduated(&market));
+        assert_eq!(get_remaining_supply_before_graduation(&market).unwrap(), 0);
+        assert_eq!(get_graduation_progress(&market).unwrap(), 100.0);
+        assert_eq!(get_quote_amount_to_graduation(&market).unwrap(), 0);
+    }
+
+    #[test]
+    fn graduation_at_zero_supply() {
+        let vm = get_vm_and_create_market();
+
+        let mut market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        market.settings = get_market_settings(&MarketSettingsInput {
+            max_supply: MAX_SUPPLY,
+            supply_at_graduation: 0,
+            sqrt_price_a_x96: SQRT_PRICE_A,
+            sqrt_price_b_x96: SQRT_PRICE_B,
+            fee: FEE,
+        })
+        .unwrap();
+
+        assert_eq!(market.settings.liquidity_a, 0);
+        assert!(!is_graduated(&market));
+        assert_eq!(get_supply_at_graduation(&market).unwrap(), 0);
+        assert_eq!(get_remaining_supply_before_graduation(&market).unwrap(), 0);
+        assert_eq!(get_graduation_progress(&market).unwrap(), 100.0);
+    }
+}
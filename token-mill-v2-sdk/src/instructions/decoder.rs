@@ -0,0 +1,548 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::error::{Result, SdkError};
+use token_mill_v2_client::{instructions::*, programs::TOKEN_MILL_V2_ID};
+
+// First 8 bytes of `sha256("global:<instruction_name>")`, as in the generated instruction data
+pub const CREATE_CONFIG_DISCRIMINATOR: [u8; 8] = [201, 207, 243, 114, 75, 111, 47, 189];
+pub const CREATE_MARKET_DISCRIMINATOR: [u8; 8] = [103, 226, 97, 235, 200, 188, 251, 254];
+pub const FORCE_REMOVE_FEE_RESERVE_DISCRIMINATOR: [u8; 8] = [171, 249, 234, 88, 247, 69, 109, 249];
+pub const REMOVE_SWAP_AUTHORITY_DISCRIMINATOR: [u8; 8] = [128, 249, 213, 153, 65, 171, 76, 171];
+pub const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+pub const SWAP_WITH_PRICE_LIMIT_DISCRIMINATOR: [u8; 8] = [54, 23, 76, 40, 64, 202, 5, 69];
+pub const TRANSFER_CONFIG_OWNERSHIP_DISCRIMINATOR: [u8; 8] = [53, 124, 67, 226, 108, 130, 19, 12];
+pub const UPDATE_CONFIG_SETTINGS_DISCRIMINATOR: [u8; 8] = [222, 242, 103, 173, 124, 98, 180, 244];
+pub const UPDATE_FEE_RESERVE_DISCRIMINATOR: [u8; 8] = [129, 136, 189, 198, 155, 88, 145, 199];
+pub const UPDATE_MARKET_DEFAULTS_DISCRIMINATOR: [u8; 8] = [38, 26, 191, 18, 30, 234, 178, 41];
+
+/// Token Mill V2 instruction, with its accounts named as in the generated instructions
+/// Remaining accounts are ignored
+#[derive(Debug)]
+pub enum TokenMillInstruction {
+    CreateConfig {
+        accounts: CreateConfig,
+        args: CreateConfigInstructionArgs,
+    },
+    CreateMarket {
+        accounts: CreateMarket,
+        args: CreateMarketInstructionArgs,
+    },
+    ForceRemoveFeeReserve {
+        accounts: ForceRemoveFeeReserve,
+    },
+    RemoveSwapAuthority {
+        accounts: RemoveSwapAuthority,
+    },
+    Swap {
+        accounts: Swap,
+        args: SwapInstructionArgs,
+    },
+    SwapWithPriceLimit {
+        accounts: SwapWithPriceLimit,
+        args: SwapWithPriceLimitInstructionArgs,
+    },
+    TransferConfigOwnership {
+        accounts: TransferConfigOwnership,
+        args: TransferConfigOwnershipInstructionArgs,
+    },
+    UpdateConfigSettings {
+        accounts: UpdateConfigSettings,
+        args: UpdateConfigSettingsInstructionArgs,
+    },
+    UpdateFeeReserve {
+        accounts: UpdateFeeReserve,
+    },
+    UpdateMarketDefaults {
+        accounts: UpdateMarketDefaults,
+        args: UpdateMarketDefaultsInstructionArgs,
+    },
+}
+
+impl TokenMillInstruction {
+    pub fn decode(instruction: &Instruction) -> Result<Self> {
+        if instruction.program_id != TOKEN_MILL_V2_ID {
+            return Err(SdkError::InvalidInstruction(format!(
+                "program id {} is not Token Mill V2",
+                instruction.program_id
+            )));
+        }
+
+        let Some((discriminator, data)) = instruction.data.split_first_chunk::<8>() else {
+            return Err(SdkError::InvalidInstruction(
+                "data is shorter than the discriminator".to_string(),
+            ));
+        };
+
+        let decoded_instruction = match *discriminator {
+            CREATE_CONFIG_DISCRIMINATOR => {
+                let [
+                    token_mill_config,
+                    admin,
+                    system_program,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::CreateConfig {
+                    accounts: CreateConfig {
+                        token_mill_config,
+                        admin,
+                        system_program,
+                        event_authority,
+                        program,
+                    },
+                    args: CreateConfigInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            CREATE_MARKET_DISCRIMINATOR => {
+                let [
+                    token_mill_config,
+                    market,
+                    token_mint0,
+                    market_reserve0,
+                    token0_metadata,
+                    token_mint1,
+                    market_reserve1,
+                    creator,
+                    system_program,
+                    token_program,
+                    token_metadata_program,
+                    associated_token_program,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::CreateMarket {
+                    accounts: CreateMarket {
+                        token_mill_config,
+                        market,
+                        token_mint0,
+                        market_reserve0,
+                        token0_metadata,
+                        token_mint1,
+                        market_reserve1,
+                        creator,
+                        system_program,
+                        token_program,
+                        token_metadata_program,
+                        associated_token_program,
+                        event_authority,
+                        program,
+                    },
+                    args: CreateMarketInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            FORCE_REMOVE_FEE_RESERVE_DISCRIMINATOR => {
+                let [config, market, admin, event_authority, program] = get_accounts(instruction)?;
+
+                Self::ForceRemoveFeeReserve {
+                    accounts: ForceRemoveFeeReserve {
+                        config,
+                        market,
+                        admin,
+                        event_authority,
+                        program,
+                    },
+                }
+            }
+            REMOVE_SWAP_AUTHORITY_DISCRIMINATOR => {
+                let [
+                    config,
+                    market,
+                    new_fee_reserve,
+                    creator,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::RemoveSwapAuthority {
+                    accounts: RemoveSwapAuthority {
+                        config,
+                        market,
+                        new_fee_reserve: get_optional_account(new_fee_reserve),
+                        creator,
+                        event_authority,
+                        program,
+                    },
+                }
+            }
+            SWAP_DISCRIMINATOR => {
+                let [
+                    config,
+                    market,
+                    market_reserve0,
+                    user_token_account0,
+                    market_reserve1,
+                    user_token_account1,
+                    fee_reserve,
+                    protocol_fee_reserve,
+                    creator_fee_pool,
+                    user,
+                    swap_authority,
+                    token_program,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::Swap {
+                    accounts: Swap {
+                        config,
+                        market,
+                        market_reserve0,
+                        user_token_account0,
+                        market_reserve1,
+                        user_token_account1,
+                        fee_reserve,
+                        protocol_fee_reserve,
+                        creator_fee_pool,
+                        user,
+                        swap_authority: get_optional_account(swap_authority),
+                        token_program,
+                        event_authority,
+                        program,
+                    },
+                    args: SwapInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            SWAP_WITH_PRICE_LIMIT_DISCRIMINATOR => {
+                let [
+                    config,
+                    market,
+                    market_reserve0,
+                    user_token_account0,
+                    market_reserve1,
+                    user_token_account1,
+                    fee_reserve,
+                    protocol_fee_reserve,
+                    creator_fee_pool,
+                    user,
+                    swap_authority,
+                    token_program,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::SwapWithPriceLimit {
+                    accounts: SwapWithPriceLimit {
+                        config,
+                        market,
+                        market_reserve0,
+                        user_token_account0,
+                        market_reserve1,
+                        user_token_account1,
+                        fee_reserve,
+                        protocol_fee_reserve,
+                        creator_fee_pool,
+                        user,
+                        swap_authority: get_optional_account(swap_authority),
+                        token_program,
+                        event_authority,
+                        program,
+                    },
+                    args: SwapWithPriceLimitInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            TRANSFER_CONFIG_OWNERSHIP_DISCRIMINATOR => {
+                let [token_mill_config, admin, event_authority, program] =
+                    get_accounts(instruction)?;
+
+                Self::TransferConfigOwnership {
+                    accounts: TransferConfigOwnership {
+                        token_mill_config,
+                        admin,
+                        event_authority,
+                        program,
+                    },
+                    args: TransferConfigOwnershipInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            UPDATE_CONFIG_SETTINGS_DISCRIMINATOR => {
+                let [
+                    token_mill_config,
+                    new_protocol_fee_reserve,
+                    new_creator_fee_pool,
+                    admin,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::UpdateConfigSettings {
+                    accounts: UpdateConfigSettings {
+                        token_mill_config,
+                        new_protocol_fee_reserve,
+                        new_creator_fee_pool,
+                        admin,
+                        event_authority,
+                        program,
+                    },
+                    args: UpdateConfigSettingsInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            UPDATE_FEE_RESERVE_DISCRIMINATOR => {
+                let [
+                    config,
+                    market,
+                    new_fee_reserve,
+                    creator,
+                    event_authority,
+                    program,
+                ] = get_accounts(instruction)?;
+
+                Self::UpdateFeeReserve {
+                    accounts: UpdateFeeReserve {
+                        config,
+                        market,
+                        new_fee_reserve: get_optional_account(new_fee_reserve),
+                        creator,
+                        event_authority,
+                        program,
+                    },
+                }
+            }
+            UPDATE_MARKET_DEFAULTS_DISCRIMINATOR => {
+                let [token_mill_config, admin, event_authority, program] =
+                    get_accounts(instruction)?;
+
+                Self::UpdateMarketDefaults {
+                    accounts: UpdateMarketDefaults {
+                        token_mill_config,
+                        admin,
+                        event_authority,
+                        program,
+                    },
+                    args: UpdateMarketDefaultsInstructionArgs::try_from_slice(data)?,
+                }
+            }
+            _ => {
+                return Err(SdkError::InvalidInstruction(format!(
+                    "unknown discriminator {discriminator:?}"
+                )));
+            }
+        };
+
+        Ok(decoded_instruction)
+    }
+}
+
+fn get_accounts<const N: usize>(instruction: &Instruction) -> Result<[Pubkey; N]> {
+    if instruction.accounts.len() < N {
+        return Err(SdkError::InvalidInstruction(format!(
+            "expected at least {N} accounts, got {}",
+            instruction.accounts.len()
+        )));
+    }
+
+    Ok(std::array::from_fn(|index| {
+        instruction.accounts[index].pubkey
+    }))
+}
+
+// Missing optional accounts are replaced by the program id
+fn get_optional_account(account: Pubkey) -> Option<Pubkey> {
+    (account != TOKEN_MILL_V2_ID).then_some(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use token_mill_v2_client::types::{MarketSettingsInput, SwapParameters};
+
+    use crate::test_utils::{
+        constants::{ALICE, BOB, CONFIG, MARKET},
+        instructions::{
+            get_create_config_ix_builder, get_market_creation_ix, get_swap_ix_builder,
+            get_swap_with_price_limit_ix_builder,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn discriminators() {
+        for (discriminator, instruction_data) in [
+            (
+                CREATE_CONFIG_DISCRIMINATOR,
+                borsh::to_vec(&CreateConfigInstructionData::new()),
+            ),
+            (
+                CREATE_MARKET_DISCRIMINATOR,
+                borsh::to_vec(&CreateMarketInstructionData::new()),
+            ),
+            (
+                FORCE_REMOVE_FEE_RESERVE_DISCRIMINATOR,
+                borsh::to_vec(&ForceRemoveFeeReserveInstructionData::new()),
+            ),
+            (
+                REMOVE_SWAP_AUTHORITY_DISCRIMINATOR,
+                borsh::to_vec(&RemoveSwapAuthorityInstructionData::new()),
+            ),
+            (
+                SWAP_DISCRIMINATOR,
+                borsh::to_vec(&SwapInstructionData::new()),
+            ),
+            (
+                SWAP_WITH_PRICE_LIMIT_DISCRIMINATOR,
+                borsh::to_vec(&SwapWithPriceLimitInstructionData::new()),
+            ),
+            (
+                TRANSFER_CONFIG_OWNERSHIP_DISCRIMINATOR,
+                borsh::to_vec(&TransferConfigOwnershipInstructionData::new()),
+            ),
+            (
+                UPDATE_CONFIG_SETTINGS_DISCRIMINATOR,
+                borsh::to_vec(&UpdateConfigSettingsInstructionData::new()),
+            ),
+            (
+                UPDATE_FEE_RESERVE_DISCRIMINATOR,
+                borsh::to_vec(&UpdateFeeReserveInstructionData::new()),
+            ),
+            (
+                UPDATE_MARKET_DEFAULTS_DISCRIMINATOR,
+                borsh::to_vec(&UpdateMarketDefaultsInstructionData::new()),
+            ),
+        ] {
+            // The discriminator is the only field of the generated instruction data
+            assert_eq!(instruction_data.unwrap(), discriminator);
+        }
+    }
+
+    #[test]
+    fn decode() {
+        let mut swap_builder = get_swap_ix_builder();
+        swap_builder.swap_parameters(SwapParameters::SellExactOut(1_000, 10));
+
+        let mut swap_with_authority_builder = get_swap_ix_builder();
+        swap_with_authority_builder
+            .swap_parameters(SwapParameters::BuyExactIn(1_000, 10))
+            .swap_authority(Some(BOB));
+
+        let mut swap_with_price_limit_builder = get_swap_with_price_limit_ix_builder();
+        swap_with_price_limit_builder
+            .zero_for_one(true)
+            .delta_amount(-1_000)
+            .sqrt_price_limit_x96(1 << 96);
+
+        let mut update_fee_reserve_builder = UpdateFeeReserveBuilder::new();
+        update_fee_reserve_builder
+            .config(CONFIG)
+            .market(MARKET)
+            .creator(ALICE);
+
+        let mut opt_in_builder = update_fee_reserve_builder.clone();
+        update_fee_reserve_builder.new_fee_reserve(Some(BOB));
+
+        let mut force_remove_fee_reserve_builder = ForceRemoveFeeReserveBuilder::new();
+        force_remove_fee_reserve_builder
+            .config(CONFIG)
+            .market(MARKET)
+            .admin(ALICE);
+
+        let mut remove_swap_authority_builder = RemoveSwapAuthorityBuilder::new();
+        remove_swap_authority_builder
+            .config(CONFIG)
+            .market(MARKET)
+            .creator(ALICE);
+
+        let mut remove_swap_authority_opt_in_builder = remove_swap_authority_builder.clone();
+        remove_swap_authority_builder.new_fee_reserve(Some(BOB));
+
+        let mut transfer_config_ownership_builder = TransferConfigOwnershipBuilder::new();
+        transfer_config_ownership_builder
+            .token_mill_config(CONFIG)
+            .admin(ALICE)
+            .new_admin(BOB);
+
+        let mut update_config_settings_builder = UpdateConfigSettingsBuilder::new();
+        update_config_settings_builder
+            .token_mill_config(CONFIG)
+            .new_protocol_fee_reserve(BOB)
+            .new_creator_fee_pool(MARKET)
+            .admin(ALICE)
+            .new_protocol_fee_share(200_000)
+            .new_fee_recipient_change_cooldown(3_600);
+
+        let mut update_market_defaults_builder = UpdateMarketDefaultsBuilder::new();
+        update_market_defaults_builder
+            .token_mill_config(CONFIG)
+            .admin(ALICE)
+            .market_settings(MarketSettingsInput {
+                max_supply: 1_000_000_000,
+                supply_at_graduation: 800_000_000,
+                sqrt_price_a_x96: 1 << 90,
+                sqrt_price_b_x96: 1 << 96,
+                fee: 10_000,
+            });
+
+        // Decoding then re-encoding gives back the same instruction
+        for instruction in [
+            get_create_config_ix_builder().instruction(),
+            get_market_creation_ix(),
+            force_remove_fee_reserve_builder.instruction(),
+            remove_swap_authority_builder.instruction(),
+            remove_swap_authority_opt_in_builder
+                .new_fee_reserve(None)
+                .instruction(),
+            swap_builder.instruction(),
+            swap_with_authority_builder.instruction(),
+            swap_with_price_limit_builder.instruction(),
+            transfer_config_ownership_builder.instruction(),
+            update_config_settings_builder.instruction(),
+            update_fee_reserve_builder.instruction(),
+            opt_in_builder.new_fee_reserve(None).instruction(),
+            update_market_defaults_builder.instruction(),
+        ] {
+            let reencoded_instruction = match TokenMillInstruction::decode(&instruction).unwrap() {
+                TokenMillInstruction::CreateConfig { accounts, args } => accounts.instruction(args),
+                TokenMillInstruction::CreateMarket { accounts, args } => accounts.instruction(args),
+                TokenMillInstruction::ForceRemoveFeeReserve { accounts } => accounts.instruction(),
+                TokenMillInstruction::RemoveSwapAuthority { accounts } => accounts.instruction(),
+                TokenMillInstruction::Swap { accounts, args } => accounts.instruction(args),
+                TokenMillInstruction::SwapWithPriceLimit { accounts, args } => {
+                    accounts.instruction(args)
+                }
+                TokenMillInstruction::TransferConfigOwnership { accounts, args } => {
+                    accounts.instruction(args)
+                }
+                TokenMillInstruction::UpdateConfigSettings { accounts, args } => {
+                    accounts.instruction(args)
+                }
+                TokenMillInstruction::UpdateFeeReserve { accounts } => accounts.instruction(),
+                TokenMillInstruction::UpdateMarketDefaults { accounts, args } => {
+                    accounts.instruction(args)
+                }
+            };
+
+            assert_eq!(reencoded_instruction, instruction);
+        }
+
+        let instruction = swap_builder.instruction();
+        assert!(matches!(
+            TokenMillInstruction::decode(&Instruction {
+                program_id: BOB,
+                ..instruction.clone()
+            }),
+            Err(SdkError::InvalidInstruction(_))
+        ));
+        assert!(matches!(
+            TokenMillInstruction::decode(&Instruction {
+                accounts: instruction.accounts[..13].to_vec(),
+                ..instruction.clone()
+            }),
+            Err(SdkError::InvalidInstruction(_))
+        ));
+        assert!(matches!(
+            TokenMillInstruction::decode(&Instruction {
+                data: [0; 8].to_vec(),
+                ..instruction.clone()
+            }),
+            Err(SdkError::InvalidInstruction(_))
+        ));
+        assert!(matches!(
+            TokenMillInstruction::decode(&Instruction {
+                data: instruction.data[..9].to_vec(),
+                ..instruction
+            }),
+            Err(SdkError::Deserialization(_))
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+use crate::error::{Result, SdkError};
+use token_mill_v2_client::{
+    accounts::{Market, TokenMillConfig},
+    instructions::{CreateMarketBuilder, SwapBuilder, SwapWithPriceLimitBuilder},
+    types::SwapParameters,
+};
+
+pub mod decoder;
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+// Metaplex metadata limits, in bytes
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Accounts derived by `create_market_ix`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateMarketAccounts {
+    pub market: Pubkey,
+    pub market_reserve0: Pubkey,
+    pub market_reserve1: Pubkey,
+    pub token0_metadata: Pubkey,
+}
+
+pub fn get_associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            &owner.to_bytes(),
+            &TOKEN_PROGRAM_ID.to_bytes(),
+            &mint.to_bytes(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn get_metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            "metadata".as_bytes(),
+            &METADATA_PROGRAM_ID.to_bytes(),
+            &mint.to_bytes(),
+        ],
+        &METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Creator fees go to the market `fee_reserve`, or to the config `creator_fee_pool` if the market is opted in KotM
+pub fn get_fee_reserve(market: &Market, creator_fee_pool: &Pubkey) -> Pubkey {
+    market.fee_reserve.unwrap_or(*creator_fee_pool)
+}
+
+pub fn get_market_address(market: &Market) -> Result<Pubkey> {
+    Ok(Market::create_pda(market.token_mint0, market.bump[0])?)
+}
+
+/// Swap builder with the market and fee accounts set, the user accounts and swap parameters are left to the caller
+pub fn get_swap_ix_builder(
+    market_address: &Pubkey,
+    market: &Market,
+    protocol_fee_reserve: &Pubkey,
+    creator_fee_pool: &Pubkey,
+) -> SwapBuilder {
+    let mut swap_builder = SwapBuilder::new();
+
+    swap_builder
+        .config(market.config)
+        .market(*market_address)
+        .market_reserve0(market.reserve0)
+        .market_reserve1(market.reserve1)
+        .fee_reserve(get_fee_reserve(market, creator_fee_pool))
+        .protocol_fee_reserve(*protocol_fee_reserve)
+        .creator_fee_pool(*creator_fee_pool)
+        .swap_authority(market.swap_authority);
+
+    swap_builder
+}
+
+/// Same as `get_swap_ix_builder`, for `swap_with_price_limit`
+pub fn get_swap_with_price_limit_ix_builder(
+    market_address: &Pubkey,
+    market: &Market,
+    protocol_fee_reserve: &Pubkey,
+    creator_fee_pool: &Pubkey,
+) -> SwapWithPriceLimitBuilder {
+    let mut swap_with_price_limit_builder = SwapWithPriceLimitBuilder::new();
+
+    swap_with_price_limit_builder
+        .config(market.config)
+        .market(*market_address)
+        .market_reserve0(market.reserve0)
+        .market_reserve1(market.reserve1)
+        .fee_reserve(get_fee_reserve(market, creator_fee_pool))
+        .protocol_fee_reserve(*protocol_fee_reserve)
+        .creator_fee_pool(*creator_fee_pool)
+        .swap_authority(market.swap_authority);
+
+    swap_with_price_limit_builder
+}
+
+/// Swap instruction using the user associated token accounts
+/// If the market has a swap authority, it must sign the transaction
+pub fn build_swap_ix(
+    market: &Market,
+    config: &TokenMillConfig,
+    user: &Pubkey,
+    swap_parameters: SwapParameters,
+) -> Result<Instruction> {
+    let instruction = get_swap_ix_builder(
+        &get_market_address(market)?,
+        market,
+        &config.protocol_fee_reserve,
+        &config.creator_fee_pool,
+    )
+    .user_token_account0(get_associated_token_address(user, &market.token_mint0))
+    .user_token_account1(get_associated_token_address(user, &market.token_mint1))
+    .user(*user)
+    .swap_parameters(swap_parameters)
+    .instruction();
+
+    Ok(instruction)
+}
+
+/// Same as `build_swap_ix`, for `swap_with_price_limit`
+pub fn build_swap_with_price_limit_ix(
+    market: &Market,
+    config: &TokenMillConfig,
+    user: &Pubkey,
+    zero_for_one: bool,
+    delta_amount: i64,
+    sqrt_price_limit_x96: u128,
+) -> Result<Instruction> {
+    let instruction = get_swap_with_price_limit_ix_builder(
+        &get_market_address(market)?,
+        market,
+        &config.protocol_fee_reserve,
+        &config.creator_fee_pool,
+    )
+    .user_token_account0(get_associated_token_address(user, &market.token_mint0))
+    .user_token_account1(get_associated_token_address(user, &market.token_mint1))
+    .user(*user)
+    .zero_for_one(zero_for_one)
+    .delta_amount(delta_amount)
+    .sqrt_price_limit_x96(sqrt_price_limit_x96)
+    .instruction();
+
+    Ok(instruction)
+}
+
+/// Market creation instruction for `token_mint0`, quoted in the config quote token
+/// Name, symbol and URI are checked against the Metaplex limits, as the metadata creation would fail otherwise
+#[allow(clippy::too_many_arguments)]
+pub fn create_market_ix(
+    config_address: &Pubkey,
+    config: &TokenMillConfig,
+    creator: &Pubkey,
+    token_mint0: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    swap_authority: Option<Pubkey>,
+) -> Result<(Instruction, CreateMarketAccounts)> {
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(SdkError::InvalidInput(format!(
+            "name must be at most {MAX_NAME_LENGTH} bytes"
+        )));
+    }
+    if symbol.len() > MAX_SYMBOL_LENGTH {
+        return Err(SdkError::InvalidInput(format!(
+            "symbol must be at most {MAX_SYMBOL_LENGTH} bytes"
+        )));
+    }
+    if uri.len() > MAX_URI_LENGTH {
+        return Err(SdkError::InvalidInput(format!(
+            "uri must be at most {MAX_URI_LENGTH} bytes"
+        )));
+    }
+
+    let market = Market::find_pda(token_mint0).0;
+    let accounts = CreateMarketAccounts {
+        market,
+        market_reserve0: get_associated_token_address(&market, token_mint0),
+        market_reserve1: get_associated_token_address(&market, &config.quote_token_mint),
+        token0_metadata: get_metadata_address(token_mint0),
+    };
+
+    let mut create_market_builder = CreateMarketBuilder::new();
+    create_market_builder
+        .token_mill_config(*config_address)
+        .name(name.to_string())
+        .symbol(symbol.to_string())
+        .uri(uri.to_string())
+        .token_mint0(*token_mint0)
+        .token0_metadata(accounts.token0_metadata)
+        .token_mint1(config.quote_token_mint)
+        .market(accounts.market)
+        .market_reserve0(accounts.market_reserve0)
+        .market_reserve1(accounts.market_reserve1)
+        .creator(*creator)
+        .token_metadata_program(METADATA_PROGRAM_ID);
+
+    if let Some(swap_authority) = swap_authority {
+        create_market_builder.swap_authority(swap_authority);
+    }
+
+    Ok((create_market_builder.instruction(), accounts))
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+    use token_mill_v2_client::types::SwapResult;
+
+    use crate::{
+        quote::{MAX_SQRT_PRICE_X96, quote},
+        test_utils::{
+            constants::{ALICE, BOB, CONFIG, MARKET, TOKEN_MINT_0, TOKEN_MINT_1},
+            instructions::{
+                get_config, get_create_config_ix_builder, get_market, get_vm_and_create_market,
+            },
+            test_vm::{create_tokens, execute_instructions, get_ata, get_vm},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn swap() {
+        let mut vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+
+        assert_eq!(get_market_address(&market).unwrap(), MARKET);
+
+        // Test utils derive the swap accounts from the expected market and config
+        assert_eq!(market, get_market());
+        assert_eq!(config, get_config());
+
+        let result = quote(&market, false, 1_000_000_000, MAX_SQRT_PRICE_X96).unwrap();
+        let instruction = build_swap_ix(
+            &market,
+            &config,
+            &ALICE,
+            SwapParameters::BuyExactIn(1_000_000_000, 0),
+        )
+        .unwrap();
+
+        let swap_result = execute_instructions(&mut vm, vec![instruction], &ALICE).unwrap();
+        let swap_result = SwapResult::try_from_slice(&swap_result.return_data.data).unwrap();
+
+        assert_eq!(swap_result.amount_in, result.amount_in);
+        assert_eq!(swap_result.amount_out, result.amount_out);
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let result = quote(&market, false, 1_000_000, MAX_SQRT_PRICE_X96).unwrap();
+        let instruction = build_swap_with_price_limit_ix(
+            &market,
+            &config,
+            &ALICE,
+            false,
+            1_000_000,
+            MAX_SQRT_PRICE_X96,
+        )
+        .unwrap();
+
+        let swap_result = execute_instructions(&mut vm, vec![instruction], &ALICE).unwrap();
+        let swap_result = SwapResult::try_from_slice(&swap_result.return_data.data).unwrap();
+
+        assert_eq!(swap_result.amount_in, result.amount_in);
+        assert_eq!(swap_result.amount_out, result.amount_out);
+    }
+
+    #[test]
+    fn create_market() {
+        let mut vm = get_vm(vec![ALICE, BOB]);
+        create_tokens(&mut vm, [TOKEN_MINT_1], vec![ALICE, BOB], vec![], None);
+        execute_instructions(
+            &mut vm,
+            vec![get_create_config_ix_builder().instruction()],
+            &ALICE,
+        )
+        .unwrap();
+
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+
+        let create_market = |name: &str, symbol: &str, uri: &str| {
+            create_market_ix(
+                &CONFIG,
+                &config,
+                &ALICE,
+                &TOKEN_MINT_0,
+                name,
+                symbol,
+                uri,
+                None,
+            )
+        };
+
+        for (name, symbol, uri) in [
+            ("a".repeat(33), "TEST".to_string(), "uri.url".to_string()),
+            (
+                "Test Market".to_string(),
+                "a".repeat(11),
+                "uri.url".to_string(),
+            ),
+            (
+                "Test Market".to_string(),
+                "TEST".to_string(),
+                "a".repeat(201),
+            ),
+        ] {
+            assert!(matches!(
+                create_market(&name, &symbol, &uri),
+                Err(SdkError::InvalidInput(_))
+            ));
+        }
+
+        let (instruction, accounts) = create_market("Test Market", "TEST", "uri.url").unwrap();
+
+        assert_eq!(accounts.market, MARKET);
+        assert_eq!(accounts.market_reserve0, get_ata(&MARKET, &TOKEN_MINT_0));
+        assert_eq!(accounts.market_reserve1, get_ata(&MARKET, &TOKEN_MINT_1));
+
+        execute_instructions(&mut vm, vec![instruction], &ALICE).unwrap();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        assert_eq!(market.reserve0, accounts.market_reserve0);
+        assert_eq!(market.reserve1, accounts.market_reserve1);
+        assert!(vm.get_account(&accounts.token0_metadata).is_some());
+    }
+}
@@ -5,11 +5,10 @@ use jupiter_amm_interface::{
 };
 use solana_sdk::pubkey::Pubkey;
 
-use crate::quote::quote;
+use crate::{instructions::get_swap_ix_builder, quote::quote, slippage::get_max_sqrt_price_limit};
 use token_mill_v2_client::{
     accounts::{Market, TokenMillConfig},
     errors::TokenMillV2Error,
-    instructions::SwapBuilder,
     types::SwapParameters,
 };
 
@@ -25,8 +24,8 @@ pub struct TokenMillV2Amm {
 
 impl Amm for TokenMillV2Amm {
     fn from_keyed_account(keyed_account: &KeyedAccount, _amm_context: &AmmContext) -> Result<Self> {
-        let data_slice: &[u8] = &keyed_account.account.data;
-        let state = Market::from_bytes(data_slice)?;
+        let state =
+            Market::from_account(&keyed_account.account.owner, &keyed_account.account.data)?;
 
         let label = "Token Mill V2".to_string();
 
@@ -59,14 +58,12 @@ impl Amm for TokenMillV2Amm {
     fn update(&mut self, account_map: &AccountMap) -> Result<()> {
         // Market
         let account = try_get_account_data(account_map, &self.key)?;
-        let mut data_slice: &[u8] = &account;
-        let market = Market::from_bytes(&mut data_slice)?;
+        let market = Market::from_account_data(account)?;
         self.market_state = market;
 
         // Config
         let account = try_get_account_data(account_map, &self.market_state.config)?;
-        let mut data_slice: &[u8] = &account;
-        let config = TokenMillConfig::from_bytes(&mut data_slice)?;
+        let config = TokenMillConfig::from_account_data(account)?;
         self.protocol_fee_reserve = config.protocol_fee_reserve;
         self.creator_fee_pool = config.creator_fee_pool;
 
@@ -89,11 +86,7 @@ impl Amm for TokenMillV2Amm {
         } else {
             -amount_i64
         };
-        let sqrt_price_limit = if zero_for_one {
-            market.settings.sqrt_price_a_x96
-        } else {
-            u128::MAX / 2
-        };
+        let sqrt_price_limit = get_max_sqrt_price_limit(market, zero_for_one);
 
         let result = quote(market, zero_for_one, delta_amount, sqrt_price_limit)?;
 
@@ -124,20 +117,14 @@ impl Amm for TokenMillV2Amm {
             ),
         };
 
-        let mut swap_ix_builder = SwapBuilder::new();
+        let mut swap_ix_builder = get_swap_ix_builder(
+            &self.key,
+            &self.market_state,
+            &self.protocol_fee_reserve,
+            &self.creator_fee_pool,
+        );
         swap_ix_builder
             .swap_parameters(SwapParameters::BuyExactIn(0, 0)) // Dummy parameters, is required by the SwapBuilder to compile the instruction
-            .config(self.market_state.config)
-            .market(self.key)
-            .market_reserve0(self.market_state.reserve0)
-            .market_reserve1(self.market_state.reserve1)
-            .fee_reserve(
-                self.market_state
-                    .fee_reserve
-                    .unwrap_or(self.creator_fee_pool),
-            )
-            .protocol_fee_reserve(self.protocol_fee_reserve)
-            .creator_fee_pool(self.creator_fee_pool)
             .user_token_account0(user_reserve_0)
             .user_token_account1(user_reserve_1)
             .user(swap_params.token_transfer_authority);
@@ -204,6 +191,22 @@ mod tests {
 
         amm.update(&account_map).unwrap();
 
+        // Other accounts are rejected
+        let config_keyed_account = KeyedAccount {
+            key: CONFIG,
+            account: vm.get_account(&CONFIG).unwrap(),
+            params: None,
+        };
+        assert!(
+            TokenMillV2Amm::from_keyed_account(
+                &config_keyed_account,
+                &AmmContext {
+                    clock_ref: ClockRef::from(vm.get_sysvar::<Clock>()),
+                },
+            )
+            .is_err()
+        );
+
         let amount_in = sol_str_to_lamports("1.0").unwrap();
         let min_amount_out = 0;
 
@@ -1,4 +1,16 @@
+pub mod curve;
+pub mod error;
+pub mod events;
+pub mod graduation;
+pub mod instructions;
+#[cfg(feature = "jupiter")]
 pub mod jupiter;
+pub mod pricing;
 pub mod quote;
+pub mod settings;
+pub mod simulator;
+pub mod slippage;
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
+#[cfg(feature = "vanity")]
 pub mod vanity;
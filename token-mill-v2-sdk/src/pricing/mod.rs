@@ -0,0 +1,109 @@
+use ruint::aliases::U256;
+
+use crate::{
+    error::{Result, SdkError},
+    quote::swap_math::SQRT_PRICE_SHIFT,
+};
+use token_mill_v2_client::{accounts::Market, errors::TokenMillV2Error::PriceOverflow};
+
+/// Converts a sqrt price to a price in token 1 per token 0, adjusted by the tokens decimals
+/// The square is computed exactly, so the only rounding happens in the final float conversions
+pub fn get_price(sqrt_price_x96: u128, decimals_0: u8, decimals_1: u8) -> f64 {
+    let price_x192 = U256::from(sqrt_price_x96) * U256::from(sqrt_price_x96);
+
+    let raw_price = f64::from(price_x192) * (-2.0 * SQRT_PRICE_SHIFT as f64).exp2();
+
+    raw_price * decimals_factor(decimals_0, decimals_1)
+}
+
+/// Converts a price in token 1 per token 0, adjusted by the tokens decimals, to a sqrt price
+/// The result is rounded down
+pub fn get_sqrt_price_x96(price: f64, decimals_0: u8, decimals_1: u8) -> Result<u128> {
+    if !price.is_finite() || price <= 0.0 {
+        return Err(SdkError::InvalidInput(
+            "price must be finite and positive".to_string(),
+        ));
+    }
+
+    let raw_price = price / decimals_factor(decimals_0, decimals_1);
+    let price_x192 = raw_price * (2.0 * SQRT_PRICE_SHIFT as f64).exp2();
+
+    let price_x192 = U256::try_from(price_x192).map_err(|_| PriceOverflow)?;
+
+    price_x192
+        .root(2)
+        .try_into()
+        .map_err(|_| PriceOverflow.into())
+}
+
+/// Current price of the market, in quote token per base token
+pub fn get_spot_price(market: &Market, decimals_0: u8, decimals_1: u8) -> f64 {
+    get_price(market.sqrt_price_x96, decimals_0, decimals_1)
+}
+
+/// Current price of the market multiplied by the max supply, in quote token
+pub fn get_fully_diluted_valuation(market: &Market, decimals_0: u8, decimals_1: u8) -> f64 {
+    let max_supply = market.settings.max_supply as f64 / 10f64.powi(decimals_0.into());
+
+    get_spot_price(market, decimals_0, decimals_1) * max_supply
+}
+
+// Raw amounts are scaled by `10^decimals`, so 1 base token is worth `10^(decimals_1 - decimals_0)` times more raw quote tokens
+fn decimals_factor(decimals_0: u8, decimals_1: u8) -> f64 {
+    10f64.powi(i32::from(decimals_0) - i32::from(decimals_1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{
+        constants::{MARKET, SQRT_PRICE_A, SQRT_PRICE_B},
+        instructions::get_vm_and_create_market,
+    };
+
+    use super::*;
+
+    #[test]
+    fn price_round_trip() {
+        for sqrt_price in [
+            SQRT_PRICE_A,
+            SQRT_PRICE_B,
+            1 << SQRT_PRICE_SHIFT,
+            u128::MAX / 2,
+        ] {
+            let price = get_price(sqrt_price, 6, 9);
+            let round_trip = get_sqrt_price_x96(price, 6, 9).unwrap();
+
+            // Only the 53 bits of precision of the float are preserved
+            assert!(sqrt_price.abs_diff(round_trip) <= sqrt_price >> 52);
+        }
+
+        // 2.8e-5 raw units, so 2.8e-8 with 6 and 9 decimals
+        assert!((get_price(SQRT_PRICE_A, 0, 0) / 2.8e-5 - 1.0).abs() < 1e-3);
+        assert!((get_price(SQRT_PRICE_A, 6, 9) / 2.8e-8 - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sqrt_price_invalid_input() {
+        for price in [0.0, -0.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                get_sqrt_price_x96(price, 6, 9),
+                Err(SdkError::InvalidInput(_))
+            ));
+        }
+
+        assert!(matches!(
+            get_sqrt_price_x96(f64::MAX, 6, 9),
+            Err(SdkError::Program(PriceOverflow))
+        ));
+    }
+
+    #[test]
+    fn fully_diluted_valuation() {
+        let vm = get_vm_and_create_market();
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        // 1e9 tokens at 2.8e-8 SOL
+        let fdv = get_fully_diluted_valuation(&market, 6, 9);
+        assert!((fdv / 28.0 - 1.0).abs() < 1e-3);
+    }
+}
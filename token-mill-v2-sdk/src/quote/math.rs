@@ -1,26 +1,92 @@
-use anyhow::Result;
-use ruint::aliases::{U256, U512};
+use crate::error::Result;
+use ruint::{
+    Uint,
+    aliases::{U256, U512},
+};
 use token_mill_v2_client::errors::TokenMillV2Error;
 
-pub fn mul_div(x: U256, y: U256, denominator: U256) -> Result<u128> {
+type U320 = Uint<320, 5>;
+
+pub fn mul_div(x: U256, y: U256, denominator: U256) -> Result<u128, TokenMillV2Error> {
     if denominator.is_zero() {
-        return Err(TokenMillV2Error::DivisionByZero.into());
+        return Err(TokenMillV2Error::DivisionByZero);
     }
 
-    let x = U512::from(x);
-    let y = U512::from(y);
-    let denominator = U512::from(denominator);
-
-    let prod = x.wrapping_mul(y);
+    if denominator > U256::from(u128::MAX) {
+        return div_wide(x.widening_mul(y), denominator);
+    }
 
-    let (quotient, _) = prod.div_rem(denominator);
+    // The quotient can't fit in 128 bits if the product is at least 2^256
+    let prod = x.checked_mul(y).ok_or(TokenMillV2Error::AmountOverflow)?;
 
-    quotient
+    (prod / denominator)
         .try_into()
-        .map_err(|_| TokenMillV2Error::AmountOverflow.into())
+        .map_err(|_| TokenMillV2Error::AmountOverflow)
+}
+
+/// Long division of a 512 bits product by a denominator above 2^128, in two 64 bits digits
+fn div_wide(prod: U512, denominator: U256) -> Result<u128, TokenMillV2Error> {
+    // Above 384 bits the quotient can't fit in 128 bits, and the product could overflow once normalized
+    if prod.bit_len() > 384 {
+        return Err(TokenMillV2Error::AmountOverflow);
+    }
+
+    // Normalized so that the denominator top bit is set, for the digit estimates
+    let shift = denominator.leading_zeros();
+    let denominator = denominator << shift;
+    let limbs = *(prod << shift).as_limbs();
+
+    // Only products below `denominator * 2^128` have a quotient fitting in 128 bits
+    let mut remainder = U256::from_limbs([limbs[2], limbs[3], limbs[4], limbs[5]]);
+    if limbs[6] != 0 || limbs[7] != 0 || remainder >= denominator {
+        return Err(TokenMillV2Error::AmountOverflow);
+    }
+
+    let high = div_digit(&mut remainder, limbs[1], denominator);
+    let low = div_digit(&mut remainder, limbs[0], denominator);
+
+    Ok((u128::from(high) << 64) | u128::from(low))
+}
+
+/// Divides `remainder * 2^64 + limb` by the normalized denominator, leaving the remainder
+/// The remainder must be below the denominator, so that the digit fits in 64 bits
+fn div_digit(remainder: &mut U256, limb: u64, denominator: U256) -> u64 {
+    let [r0, r1, r2, r3] = *remainder.as_limbs();
+    let [_, _, d2, d3] = *denominator.as_limbs();
+
+    // Estimated from the top limbs, at most 2 above the digit, then at most 1 after the correction
+    let top = (u128::from(r3) << 64) | u128::from(r2);
+    let (mut digit, mut digit_remainder) = if r3 == d3 {
+        (u64::MAX, top - u128::from(u64::MAX) * u128::from(d3))
+    } else {
+        let digit = top / u128::from(d3);
+        (digit as u64, top - digit * u128::from(d3))
+    };
+
+    while digit_remainder <= u128::from(u64::MAX)
+        && u128::from(digit) * u128::from(d2) > (digit_remainder << 64) | u128::from(r1)
+    {
+        digit -= 1;
+        digit_remainder += u128::from(d3);
+    }
+
+    let numerator = U320::from_limbs([limb, r0, r1, r2, r3]);
+    let denominator = U320::from(denominator);
+    let (mut difference, borrow) =
+        numerator.overflowing_sub(denominator.wrapping_mul(U320::from(digit)));
+
+    if borrow {
+        digit -= 1;
+        difference = difference.wrapping_add(denominator);
+    }
+
+    let [l0, l1, l2, l3, _] = *difference.as_limbs();
+    *remainder = U256::from_limbs([l0, l1, l2, l3]);
+
+    digit
 }
 
-pub fn mul_div_round_up(x: U256, y: U256, denominator: U256) -> Result<u128> {
+pub fn mul_div_round_up(x: U256, y: U256, denominator: U256) -> Result<u128, TokenMillV2Error> {
     let result = mul_div(x, y, denominator)?;
 
     if (x % denominator).is_zero() {
@@ -28,6 +94,129 @@ pub fn mul_div_round_up(x: U256, y: U256, denominator: U256) -> Result<u128> {
     } else {
         result
             .checked_add(1)
-            .ok_or(TokenMillV2Error::AmountOverflow.into())
+            .ok_or(TokenMillV2Error::AmountOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Previous implementation, promoting every product to 512 bits
+    fn mul_div_reference(x: U256, y: U256, denominator: U256) -> Result<u128, TokenMillV2Error> {
+        if denominator.is_zero() {
+            return Err(TokenMillV2Error::DivisionByZero);
+        }
+
+        let prod = U512::from(x).wrapping_mul(U512::from(y));
+        let (quotient, _) = prod.div_rem(U512::from(denominator));
+
+        quotient
+            .try_into()
+            .map_err(|_| TokenMillV2Error::AmountOverflow)
+    }
+
+    fn mul_div_round_up_reference(
+        x: U256,
+        y: U256,
+        denominator: U256,
+    ) -> Result<u128, TokenMillV2Error> {
+        let result = mul_div_reference(x, y, denominator)?;
+
+        if (x % denominator).is_zero() {
+            Ok(result)
+        } else {
+            result
+                .checked_add(1)
+                .ok_or(TokenMillV2Error::AmountOverflow)
+        }
+    }
+
+    fn assert_equivalent(x: U256, y: U256, denominator: U256) {
+        assert_eq!(
+            mul_div(x, y, denominator),
+            mul_div_reference(x, y, denominator),
+            "mul_div({x}, {y}, {denominator})"
+        );
+        assert_eq!(
+            mul_div_round_up(x, y, denominator),
+            mul_div_round_up_reference(x, y, denominator),
+            "mul_div_round_up({x}, {y}, {denominator})"
+        );
+    }
+
+    /// Values around powers of two, including the 128 and 256 bits bounds
+    fn edge_values() -> Vec<U256> {
+        let mut values = vec![U256::ZERO, U256::MAX];
+
+        for shift in [1, 32, 63, 64, 96, 127, 128, 129, 160, 192, 255] {
+            let power = U256::ONE << shift;
+            values.extend([power - U256::ONE, power, power + U256::ONE]);
+        }
+
+        values.extend([U256::from(3), U256::from(u128::MAX) * U256::from(3)]);
+
+        values
+    }
+
+    #[test]
+    fn mul_div_small_values() {
+        for x in 0..64u64 {
+            for y in 0..64u64 {
+                for denominator in 0..64u64 {
+                    assert_equivalent(U256::from(x), U256::from(y), U256::from(denominator));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mul_div_edge_values() {
+        let values = edge_values();
+
+        for &x in &values {
+            for &y in &values {
+                for &denominator in &values {
+                    assert_equivalent(x, y, denominator);
+                }
+            }
+        }
+    }
+
+    /// Values of random bit lengths, so that products both fit and overflow 256 bits
+    fn u256() -> impl Strategy<Value = U256> {
+        (any::<[u64; 4]>(), 0usize..=256)
+            .prop_map(|(limbs, bits)| U256::from_limbs(limbs) >> (256 - bits))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100_000))]
+
+        #[test]
+        fn mul_div_matches_reference(x in u256(), y in u256(), denominator in u256()) {
+            assert_equivalent(x, y, denominator);
+        }
+
+        /// Denominators giving quotients around the 128 bits bound
+        #[test]
+        fn mul_div_matches_reference_near_overflow(
+            x in u256(),
+            y in u256(),
+            quotient_bits in 120usize..=136,
+            offset in -2i8..=2,
+        ) {
+            let quotient = U512::ONE << quotient_bits;
+            let denominator =
+                U256::saturating_from(U512::from(x).wrapping_mul(U512::from(y)) / quotient);
+            let denominator = if offset < 0 {
+                denominator.saturating_sub(U256::from(offset.unsigned_abs()))
+            } else {
+                denominator.saturating_add(U256::from(offset))
+            };
+
+            assert_equivalent(x, y, denominator);
+        }
     }
 }
@@ -1,13 +1,20 @@
-use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
 
-use crate::quote::swap_math::get_delta_amounts;
+use crate::{
+    error::Result,
+    instructions::get_fee_reserve,
+    pricing::get_price,
+    quote::swap_math::{MAX_FEE_U128, get_delta_amounts},
+};
 use token_mill_v2_client::{
-    accounts::Market,
-    errors::TokenMillV2Error::{AmountOverflow, AmountUnderflow},
+    accounts::{Market, TokenMillConfig},
+    errors::TokenMillV2Error::{
+        AmountInOverflow, AmountOverflow, AmountUnderflow, InvalidSqrtPriceLimit,
+    },
 };
 
-mod math;
-mod swap_math;
+pub(crate) mod math;
+pub mod swap_math;
 
 #[derive(Debug, Clone)]
 pub struct Quote {
@@ -15,9 +22,87 @@ pub struct Quote {
     pub amount_out: u64,
     pub fee_amount_token_in: u64,
     pub fee_amount_token_1: u64,
+    /// Market sqrt price the quote was made at
+    pub sqrt_price_before_x96: u128,
     pub next_sqrt_price: u128,
+    /// Whether token 0 is swapped for token 1, i.e. a sell
+    pub zero_for_one: bool,
+    /// Whether the quoted amount is the amount in, i.e. `delta_amount` was positive
+    pub exact_in: bool,
+    /// Whether the swap moved the price across `sqrt_price_b_x96`, switching between pool A and B
+    pub phase_crossed: bool,
+}
+
+// Prices are computed on demand, as the float conversions aren't needed to route or execute swaps
+// They are in raw token 1 per raw token 0, see `pricing` to adjust them by the decimals
+impl Quote {
+    pub fn spot_price_before(&self) -> f64 {
+        get_price(self.sqrt_price_before_x96, 0, 0)
+    }
+
+    pub fn spot_price_after(&self) -> f64 {
+        get_price(self.next_sqrt_price, 0, 0)
+    }
+
+    /// Price paid or received for token 0, fees included
+    pub fn effective_price(&self) -> f64 {
+        let (amount_0, amount_0_without_fee, amount_1, _) = self.get_amounts();
+
+        if amount_0_without_fee == 0 {
+            return self.spot_price_before();
+        }
+
+        amount_1 as f64 / amount_0 as f64
+    }
+
+    /// Difference between the price of token 0, fees excluded, and the spot price before the swap
+    /// Positive for buys, as the price paid rises, and negative for sells, as the price received falls
+    pub fn price_impact_bps(&self) -> f64 {
+        let (_, amount_0_without_fee, _, amount_1_without_fee) = self.get_amounts();
+
+        if amount_0_without_fee == 0 {
+            return 0.0;
+        }
+
+        let spot_price_before = self.spot_price_before();
+        let execution_price = amount_1_without_fee as f64 / amount_0_without_fee as f64;
+
+        (execution_price - spot_price_before) * 10_000.0 / spot_price_before
+    }
+
+    // Amounts of token 0 and 1, with and without the fee
+    fn get_amounts(&self) -> (u64, u64, u64, u64) {
+        if self.zero_for_one {
+            (
+                self.amount_in,
+                self.amount_in - self.fee_amount_token_in,
+                self.amount_out,
+                self.amount_out,
+            )
+        } else {
+            (
+                self.amount_out,
+                self.amount_out,
+                self.amount_in,
+                self.amount_in - self.fee_amount_token_in,
+            )
+        }
+    }
+}
+
+/// Split of `Quote::fee_amount_token_1` between the protocol and the creator
+#[derive(Debug, Clone)]
+pub struct FeeSplit {
+    pub protocol_fee_amount: u64,
+    pub protocol_fee_reserve: Pubkey,
+    pub creator_fee_amount: u64,
+    /// Market `fee_reserve`, or the config `creator_fee_pool` if the market is opted in KotM
+    pub creator_fee_reserve: Pubkey,
 }
 
+/// Highest sqrt price limit accepted by the program for buys
+pub const MAX_SQRT_PRICE_X96: u128 = u128::MAX / 2;
+
 #[derive(Debug, Clone, PartialEq)]
 enum Phase {
     A,
@@ -63,10 +148,84 @@ pub fn quote(
         amount_out,
         fee_amount_token_in,
         fee_amount_token_1,
+        sqrt_price_before_x96: market.sqrt_price_x96,
         next_sqrt_price,
+        zero_for_one,
+        exact_in: delta_amount.is_positive(),
+        phase_crossed: get_phase(market, market.sqrt_price_x96)
+            != get_phase(market, next_sqrt_price),
+    })
+}
+
+/// Quotes the swap moving the market price to `target_sqrt_price_x96`, as executed by
+/// `swap_with_price_limit` with an unbounded amount.
+/// For sells, the fee is swapped after the limit is reached, so `next_sqrt_price` ends slightly below the target.
+pub fn quote_to_price(market: &Market, target_sqrt_price_x96: u128) -> Result<Quote> {
+    let zero_for_one = target_sqrt_price_x96 < market.sqrt_price_x96;
+
+    if target_sqrt_price_x96 == market.sqrt_price_x96
+        || target_sqrt_price_x96 < market.settings.sqrt_price_a_x96
+        || target_sqrt_price_x96 > MAX_SQRT_PRICE_X96
+    {
+        return Err(InvalidSqrtPriceLimit.into());
+    }
+
+    let result = quote(market, zero_for_one, i64::MAX, target_sqrt_price_x96)?;
+
+    // The whole amount was consumed, the target can't be reached
+    if result.amount_in == i64::MAX as u64 {
+        return Err(AmountInOverflow.into());
+    }
+
+    Ok(result)
+}
+
+/// Same as `quote`, with the fee split between the protocol and the creator
+pub fn quote_with_config(
+    market: &Market,
+    config: &TokenMillConfig,
+    zero_for_one: bool,
+    delta_amount: i64,
+    sqrt_price_limit: u128,
+) -> Result<(Quote, FeeSplit)> {
+    let result = quote(market, zero_for_one, delta_amount, sqrt_price_limit)?;
+    let fee_split = get_fee_split(market, config, result.fee_amount_token_1)?;
+
+    Ok((result, fee_split))
+}
+
+/// The protocol share is rounded down, the creator gets the rest
+pub fn get_fee_split(
+    market: &Market,
+    config: &TokenMillConfig,
+    fee_amount_token_1: u64,
+) -> Result<FeeSplit> {
+    let protocol_fee_amount = u128::from(fee_amount_token_1)
+        .checked_mul(config.protocol_fee_share.into())
+        .ok_or(AmountOverflow)?
+        / MAX_FEE_U128;
+
+    // Safe cast as protocol_fee_share <= MAX_FEE
+    let protocol_fee_amount = protocol_fee_amount as u64;
+
+    Ok(FeeSplit {
+        protocol_fee_amount,
+        protocol_fee_reserve: config.protocol_fee_reserve,
+        creator_fee_amount: fee_amount_token_1
+            .checked_sub(protocol_fee_amount)
+            .ok_or(AmountUnderflow)?,
+        creator_fee_reserve: get_fee_reserve(market, &config.creator_fee_pool),
     })
 }
 
+fn get_phase(market: &Market, sqrt_price: u128) -> Phase {
+    if sqrt_price < market.settings.sqrt_price_b_x96 {
+        Phase::A
+    } else {
+        Phase::B
+    }
+}
+
 fn get_delta_amounts_from_dual_pool(
     market: &Market,
     current_sqrt_price: u128,
@@ -75,11 +234,7 @@ fn get_delta_amounts_from_dual_pool(
     sqrt_price_limit: u128,
     fee: u32,
 ) -> Result<(u128, u64, u64, u64)> {
-    let phase = if current_sqrt_price < market.settings.sqrt_price_b_x96 {
-        Phase::A
-    } else {
-        Phase::B
-    };
+    let phase = get_phase(market, current_sqrt_price);
 
     let (first_l, second_l) = match phase {
         Phase::A => (market.settings.liquidity_a, market.settings.liquidity_b),
@@ -87,7 +242,7 @@ fn get_delta_amounts_from_dual_pool(
     };
 
     // First pool
-    let first_sqrt_price_target = if zero_for_one == false {
+    let first_sqrt_price_target = if !zero_for_one {
         if phase == Phase::A {
             sqrt_price_limit.min(market.settings.sqrt_price_b_x96)
         } else {
@@ -157,3 +312,689 @@ fn get_delta_amounts_from_dual_pool(
         fee_amount,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use borsh::BorshDeserialize;
+    use litesvm::LiteSVM;
+    use litesvm_token::spl_token::error::TokenError;
+    use proptest::{
+        prelude::*,
+        test_runner::{Config, TestRunner},
+    };
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        instruction::{Instruction, InstructionError},
+        native_token::sol_str_to_lamports,
+        transaction::TransactionError,
+    };
+    use token_mill_v2_client::types::{MarketSettingsInput, SwapParameters, SwapResult};
+
+    use token_mill_v2_client::instructions::UpdateFeeReserveBuilder;
+
+    use crate::test_utils::{
+        constants::{
+            ALICE, BOB, CONFIG, FEE_UPDATE_COOLDOWN, MARKET, SQRT_PRICE_A, SQRT_PRICE_B,
+            TOKEN_MINT_0, TOKEN_MINT_1,
+        },
+        instructions::{
+            get_create_config_ix_builder, get_market_creation_ix,
+            get_swap_with_price_limit_ix_builder, get_vm_and_create_market, parse_error,
+        },
+        test_vm::{
+            create_atas, create_tokens, execute_instructions, get_ata, get_token_balance, get_vm,
+            make_address, set_token_balance, warp,
+        },
+    };
+    use crate::{
+        error::SdkError,
+        instructions::{
+            build_swap_ix, build_swap_with_price_limit_ix, create_market_ix, get_market_address,
+        },
+        settings::get_market_settings,
+        simulator::MarketSimulator,
+    };
+
+    use super::*;
+
+    #[test]
+    fn quote_to_price_matches_program() {
+        let mut vm = get_vm_and_create_market();
+
+        // Buy across the pool A/B boundary, then sell back into pool A
+        for target_sqrt_price in [SQRT_PRICE_B + SQRT_PRICE_A, SQRT_PRICE_B - SQRT_PRICE_A] {
+            let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+            let zero_for_one = target_sqrt_price < market.sqrt_price_x96;
+
+            let quote = quote_to_price(&market, target_sqrt_price).unwrap();
+
+            let mut swap_builder = get_swap_with_price_limit_ix_builder();
+            swap_builder
+                .zero_for_one(zero_for_one)
+                .delta_amount(i64::MAX)
+                .sqrt_price_limit_x96(target_sqrt_price);
+
+            let result =
+                execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+            let result = SwapResult::try_from_slice(&result.return_data.data).unwrap();
+
+            assert_eq!(result.amount_in, quote.amount_in);
+            assert_eq!(result.amount_out, quote.amount_out);
+            assert_eq!(result.fee_amount_token_in, quote.fee_amount_token_in);
+            assert_eq!(result.fee_amount_token1, quote.fee_amount_token_1);
+
+            let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+            assert_eq!(market.sqrt_price_x96, quote.next_sqrt_price);
+        }
+    }
+
+    #[test]
+    fn price_impact() {
+        let vm = get_vm_and_create_market();
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        let small_buy = quote(&market, false, 1_000_000, MAX_SQRT_PRICE_X96).unwrap();
+        let large_buy = quote(&market, false, 100_000_000_000, MAX_SQRT_PRICE_X96).unwrap();
+
+        assert!(!small_buy.phase_crossed);
+        assert!(large_buy.phase_crossed);
+
+        for result in [&small_buy, &large_buy] {
+            assert_eq!(result.spot_price_before(), get_price(SQRT_PRICE_A, 0, 0));
+            assert_eq!(
+                result.spot_price_after(),
+                get_price(result.next_sqrt_price, 0, 0)
+            );
+            assert_eq!(
+                result.effective_price(),
+                result.amount_in as f64 / result.amount_out as f64
+            );
+            // The fee is paid on top of the price impact
+            assert!(
+                result.effective_price()
+                    > result.spot_price_before() * (1.0 + result.price_impact_bps() / 10_000.0)
+            );
+        }
+
+        assert!(0.0 < small_buy.price_impact_bps() && small_buy.price_impact_bps() < 1.0);
+        assert!(large_buy.price_impact_bps() > 10_000.0);
+
+        // Selling back the large buy, from the price it reached
+        let mut market_after = market.clone();
+        market_after.sqrt_price_x96 = large_buy.next_sqrt_price;
+        let sell = quote(
+            &market_after,
+            true,
+            large_buy.amount_out as i64,
+            SQRT_PRICE_A,
+        )
+        .unwrap();
+
+        assert_eq!(sell.spot_price_before(), large_buy.spot_price_after());
+        assert!(sell.phase_crossed);
+        assert!(sell.price_impact_bps() < 0.0);
+        // The fee is paid on top of the price impact
+        assert!(
+            sell.effective_price()
+                < sell.spot_price_before() * (1.0 + sell.price_impact_bps() / 10_000.0)
+        );
+
+        let no_swap = quote(&market, false, 0, MAX_SQRT_PRICE_X96).unwrap();
+        assert_eq!(no_swap.effective_price(), no_swap.spot_price_before());
+        assert_eq!(no_swap.price_impact_bps(), 0.0);
+    }
+
+    #[test]
+    fn fee_split_matches_program() {
+        let protocol = make_address("protocol");
+        let kotm = make_address("kotm");
+        let creator = make_address("creator");
+
+        let mut vm = get_vm(vec![ALICE, BOB]);
+        create_tokens(
+            &mut vm,
+            [TOKEN_MINT_1],
+            vec![ALICE, BOB],
+            vec![protocol, kotm, creator],
+            None,
+        );
+
+        let mut create_config_builder = get_create_config_ix_builder();
+        create_config_builder
+            .protocol_fee_token_account(get_ata(&protocol, &TOKEN_MINT_1))
+            .kotm_fee_token_account(get_ata(&kotm, &TOKEN_MINT_1));
+
+        execute_instructions(
+            &mut vm,
+            vec![
+                create_config_builder.instruction(),
+                get_market_creation_ix(),
+            ],
+            &ALICE,
+        )
+        .unwrap();
+        create_atas(&mut vm, vec![TOKEN_MINT_0], vec![ALICE]);
+
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+
+        for (zero_for_one, delta_amount, fee_reserve) in [
+            (false, 1_234_567, None),
+            (true, 1_234_567_891, None),
+            (false, 1_234_567, Some(get_ata(&creator, &TOKEN_MINT_1))),
+        ] {
+            if fee_reserve.is_some() {
+                warp(&mut vm, FEE_UPDATE_COOLDOWN.into());
+
+                let mut update_fee_reserve_builder = UpdateFeeReserveBuilder::new();
+                update_fee_reserve_builder
+                    .config(CONFIG)
+                    .market(MARKET)
+                    .new_fee_reserve(fee_reserve)
+                    .creator(ALICE);
+
+                execute_instructions(
+                    &mut vm,
+                    vec![update_fee_reserve_builder.instruction()],
+                    &ALICE,
+                )
+                .unwrap();
+            }
+
+            let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+            let sqrt_price_limit = if zero_for_one {
+                SQRT_PRICE_A
+            } else {
+                MAX_SQRT_PRICE_X96
+            };
+
+            let (_, fee_split) = quote_with_config(
+                &market,
+                &config,
+                zero_for_one,
+                delta_amount,
+                sqrt_price_limit,
+            )
+            .unwrap();
+
+            assert_eq!(
+                fee_split.creator_fee_reserve,
+                fee_reserve.unwrap_or(get_ata(&kotm, &TOKEN_MINT_1))
+            );
+
+            let balances_before = [
+                get_token_balance(&vm, &protocol, &TOKEN_MINT_1),
+                get_token_balance(&vm, &kotm, &TOKEN_MINT_1),
+                get_token_balance(&vm, &creator, &TOKEN_MINT_1),
+            ];
+
+            let mut swap_builder = get_swap_with_price_limit_ix_builder();
+            swap_builder
+                .protocol_fee_reserve(config.protocol_fee_reserve)
+                .creator_fee_pool(config.creator_fee_pool)
+                .fee_reserve(fee_split.creator_fee_reserve)
+                .zero_for_one(zero_for_one)
+                .delta_amount(delta_amount)
+                .sqrt_price_limit_x96(sqrt_price_limit);
+
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+
+            let balances_after = [
+                get_token_balance(&vm, &protocol, &TOKEN_MINT_1),
+                get_token_balance(&vm, &kotm, &TOKEN_MINT_1),
+                get_token_balance(&vm, &creator, &TOKEN_MINT_1),
+            ];
+
+            let creator_fee_index = if fee_reserve.is_some() { 2 } else { 1 };
+
+            assert_eq!(
+                balances_after[0] - balances_before[0],
+                fee_split.protocol_fee_amount
+            );
+            assert_eq!(
+                balances_after[creator_fee_index] - balances_before[creator_fee_index],
+                fee_split.creator_fee_amount
+            );
+        }
+    }
+
+    #[test]
+    fn quote_to_price_invalid_target() {
+        let vm = get_vm_and_create_market();
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        for target_sqrt_price in [
+            market.sqrt_price_x96,
+            SQRT_PRICE_A - 1,
+            MAX_SQRT_PRICE_X96 + 1,
+        ] {
+            let err = quote_to_price(&market, target_sqrt_price).unwrap_err();
+
+            assert_eq!(err.program_error(), Some(&InvalidSqrtPriceLimit));
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum PriceLimit {
+        /// Curve bound in the swap direction
+        Max,
+        /// Current price moved by `sqrt_price >> shift` in the swap direction
+        Relative(u32),
+        Raw(u128),
+    }
+
+    fn market_settings_input() -> impl Strategy<Value = MarketSettingsInput> {
+        (
+            prop_oneof![Just(SQRT_PRICE_A), (1u128 << 40)..(1u128 << 100)],
+            1u128..=64,
+            prop_oneof![Just(1_000_000_000_000_000), 1u64..=u64::MAX / 4],
+            0u64..=100,
+            prop_oneof![Just(0), Just(10_000), 0u32..1_000_000],
+        )
+            .prop_map(
+                |(sqrt_price_a_x96, ratio_x8, max_supply, graduation_percent, fee)| {
+                    MarketSettingsInput {
+                        max_supply,
+                        supply_at_graduation: (u128::from(max_supply)
+                            * u128::from(graduation_percent)
+                            / 100) as u64,
+                        sqrt_price_a_x96,
+                        // Below sqrt_price_a_x96 when ratio_x8 < 8, to also cover invalid settings
+                        sqrt_price_b_x96: sqrt_price_a_x96 * ratio_x8 / 8,
+                        fee,
+                    }
+                },
+            )
+    }
+
+    #[derive(Debug, Clone)]
+    enum AmountLimit {
+        /// No slippage protection, a zero amount out or an unbounded amount in
+        None,
+        /// Amount quoted without limit moved by the offset, to land on both sides of the slippage check
+        Quoted(i64),
+        Raw(u64),
+    }
+
+    #[derive(Debug, Clone)]
+    enum SwapCase {
+        Swap {
+            zero_for_one: bool,
+            exact_in: bool,
+            amount: u64,
+            amount_limit: AmountLimit,
+        },
+        SwapWithPriceLimit {
+            zero_for_one: bool,
+            delta_amount: i64,
+            price_limit: PriceLimit,
+        },
+    }
+
+    fn swap() -> impl Strategy<Value = SwapCase> {
+        prop_oneof![swap_with_parameters(), swap_with_price_limit()]
+    }
+
+    fn swap_with_parameters() -> impl Strategy<Value = SwapCase> {
+        (
+            any::<bool>(),
+            any::<bool>(),
+            prop_oneof![
+                Just(0),
+                Just(i64::MAX as u64),
+                Just(i64::MAX as u64 + 1),
+                1..1_000u64,
+                1..1_000_000_000_000_000u64,
+                any::<u64>(),
+            ],
+            prop_oneof![
+                Just(AmountLimit::None),
+                (-2..=2i64).prop_map(AmountLimit::Quoted),
+                any::<i64>().prop_map(AmountLimit::Quoted),
+                any::<u64>().prop_map(AmountLimit::Raw),
+            ],
+        )
+            .prop_map(
+                |(zero_for_one, exact_in, amount, amount_limit)| SwapCase::Swap {
+                    zero_for_one,
+                    exact_in,
+                    amount,
+                    amount_limit,
+                },
+            )
+    }
+
+    fn swap_with_price_limit() -> impl Strategy<Value = SwapCase> {
+        (
+            any::<bool>(),
+            prop_oneof![
+                Just(0),
+                Just(i64::MAX),
+                -1_000..1_000i64,
+                -1_000_000_000_000_000..1_000_000_000_000_000i64,
+                any::<i64>(),
+            ],
+            prop_oneof![
+                Just(PriceLimit::Max),
+                (0u32..64).prop_map(PriceLimit::Relative),
+                any::<u128>().prop_map(PriceLimit::Raw),
+            ],
+        )
+            .prop_map(|(zero_for_one, delta_amount, price_limit)| {
+                SwapCase::SwapWithPriceLimit {
+                    zero_for_one,
+                    delta_amount,
+                    price_limit,
+                }
+            })
+    }
+
+    fn get_swap_parameters(
+        zero_for_one: bool,
+        exact_in: bool,
+        amount: u64,
+        amount_limit: u64,
+    ) -> SwapParameters {
+        match (zero_for_one, exact_in) {
+            (false, true) => SwapParameters::BuyExactIn(amount, amount_limit),
+            (false, false) => SwapParameters::BuyExactOut(amount_limit, amount),
+            (true, true) => SwapParameters::SellExactIn(amount, amount_limit),
+            (true, false) => SwapParameters::SellExactOut(amount_limit, amount),
+        }
+    }
+
+    fn get_amount_limit(
+        simulator: &MarketSimulator,
+        zero_for_one: bool,
+        exact_in: bool,
+        amount: u64,
+        amount_limit: &AmountLimit,
+    ) -> u64 {
+        let no_limit = if exact_in { 0 } else { u64::MAX };
+
+        match *amount_limit {
+            AmountLimit::None => no_limit,
+            AmountLimit::Quoted(offset) => {
+                let swap_parameters = get_swap_parameters(zero_for_one, exact_in, amount, no_limit);
+
+                match simulator.clone().swap(&swap_parameters) {
+                    Ok((quote, _)) if exact_in => quote.amount_out.saturating_add_signed(offset),
+                    Ok((quote, _)) => quote.amount_in.saturating_add_signed(offset),
+                    Err(_) => no_limit,
+                }
+            }
+            AmountLimit::Raw(amount_limit) => amount_limit,
+        }
+    }
+
+    fn get_sqrt_price_limit(market: &Market, zero_for_one: bool, price_limit: &PriceLimit) -> u128 {
+        let sqrt_price = market.sqrt_price_x96;
+
+        match *price_limit {
+            PriceLimit::Max if zero_for_one => market.settings.sqrt_price_a_x96,
+            PriceLimit::Max => MAX_SQRT_PRICE_X96,
+            PriceLimit::Relative(shift) if zero_for_one => sqrt_price - (sqrt_price >> shift),
+            PriceLimit::Relative(shift) => sqrt_price.saturating_add(sqrt_price >> shift),
+            PriceLimit::Raw(sqrt_price_limit) => sqrt_price_limit,
+        }
+    }
+
+    // Market creation and large swaps can exceed the default limit, depending on the PDA bumps and amounts
+    fn with_compute_unit_limit(instruction: Instruction) -> Vec<Instruction> {
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            instruction,
+        ]
+    }
+
+    fn create_market(
+        vm: &mut LiteSVM,
+        input: &MarketSettingsInput,
+    ) -> std::result::Result<Option<MarketSimulator>, TestCaseError> {
+        let config_address = Pubkey::new_unique();
+        let token_mint0 = Pubkey::new_unique();
+
+        // Rent for the new accounts, as cases share the VM
+        vm.airdrop(&ALICE, sol_str_to_lamports("1.0").unwrap())
+            .unwrap();
+
+        let mut create_config_builder = get_create_config_ix_builder();
+        create_config_builder
+            .token_mill_config(config_address)
+            .market_settings(input.clone());
+
+        let instructions = vec![create_config_builder.instruction()];
+        let result = execute_instructions(vm, instructions.clone(), &ALICE);
+
+        // Invalid settings are rejected by both
+        if let Err(err) = get_market_settings(input) {
+            prop_assert_eq!(
+                err.program_error().cloned(),
+                parse_error(&instructions, result).ok()
+            );
+            return Ok(None);
+        }
+        prop_assert!(result.is_ok(), "{:?}", result.unwrap_err().err);
+
+        let config =
+            TokenMillConfig::from_bytes(&vm.get_account(&config_address).unwrap().data).unwrap();
+        let (instruction, accounts) = create_market_ix(
+            &config_address,
+            &config,
+            &ALICE,
+            &token_mint0,
+            "Test Market",
+            "TEST",
+            "uri.url",
+            None,
+        )
+        .unwrap();
+
+        execute_instructions(vm, with_compute_unit_limit(instruction), &ALICE).unwrap();
+
+        create_atas(vm, vec![token_mint0], vec![ALICE]);
+
+        let market = Market::from_bytes(&vm.get_account(&accounts.market).unwrap().data).unwrap();
+
+        Ok(Some(MarketSimulator::new(
+            market,
+            config,
+            get_token_balance(vm, &accounts.market, &token_mint0),
+            0,
+        )))
+    }
+
+    // Executes the swap with the simulator and the program, and checks they end in the same state
+    fn check_swap(
+        vm: &mut LiteSVM,
+        simulator: &mut MarketSimulator,
+        swap: SwapCase,
+    ) -> std::result::Result<(), TestCaseError> {
+        let market = simulator.market.clone();
+
+        // As much as the token supplies allow, and room for the fees
+        // The user and fee balances aren't under test
+        let balance0 = u64::MAX - simulator.reserve0;
+        let balance1 = u64::MAX - simulator.reserve1;
+        set_token_balance(vm, &ALICE, &market.token_mint0, balance0);
+        set_token_balance(vm, &ALICE, &market.token_mint1, balance1);
+        set_token_balance(vm, &BOB, &market.token_mint1, 0);
+
+        let (zero_for_one, expected, instruction) = match swap {
+            SwapCase::Swap {
+                zero_for_one,
+                exact_in,
+                amount,
+                amount_limit,
+            } => {
+                let amount_limit =
+                    get_amount_limit(simulator, zero_for_one, exact_in, amount, &amount_limit);
+                let swap_parameters =
+                    get_swap_parameters(zero_for_one, exact_in, amount, amount_limit);
+
+                (
+                    zero_for_one,
+                    simulator.swap(&swap_parameters),
+                    build_swap_ix(&market, &simulator.config, &ALICE, swap_parameters),
+                )
+            }
+            SwapCase::SwapWithPriceLimit {
+                zero_for_one,
+                delta_amount,
+                price_limit,
+            } => {
+                let sqrt_price_limit = get_sqrt_price_limit(&market, zero_for_one, &price_limit);
+
+                (
+                    zero_for_one,
+                    simulator.swap_with_price_limit(zero_for_one, delta_amount, sqrt_price_limit),
+                    build_swap_with_price_limit_ix(
+                        &market,
+                        &simulator.config,
+                        &ALICE,
+                        zero_for_one,
+                        delta_amount,
+                        sqrt_price_limit,
+                    ),
+                )
+            }
+        };
+
+        // Amounts in above the token supply can't be executed
+        if let Ok((quote, _)) = &expected {
+            let balance = if zero_for_one { balance0 } else { balance1 };
+            prop_assume!(quote.amount_in <= balance);
+        }
+
+        let instructions = with_compute_unit_limit(instruction.unwrap());
+        let result = execute_instructions(vm, instructions.clone(), &ALICE);
+
+        match expected {
+            Ok((quote, _)) => {
+                prop_assert!(result.is_ok(), "{:?}", result.unwrap_err().err);
+
+                let result = SwapResult::try_from_slice(&result.unwrap().return_data.data).unwrap();
+
+                prop_assert_eq!(result.amount_in, quote.amount_in);
+                prop_assert_eq!(result.amount_out, quote.amount_out);
+                prop_assert_eq!(result.fee_amount_token_in, quote.fee_amount_token_in);
+                prop_assert_eq!(result.fee_amount_token1, quote.fee_amount_token_1);
+            }
+            Err(err) => {
+                prop_assert!(result.is_err(), "{:?}", err);
+
+                match parse_error(&instructions, result) {
+                    Ok(error) => prop_assert_eq!(err.program_error(), Some(&error)),
+                    // Anchor `InvalidNumericConversion`, for fees above i64::MAX
+                    Err(TransactionError::InstructionError(_, InstructionError::Custom(4102))) => {
+                        prop_assert!(matches!(err, SdkError::Conversion(_)), "{:?}", err)
+                    }
+                    // Alice holds the rest of the token supply, so the transfer of an amount in
+                    // overflowing the reserve fails on her balance in the token program
+                    Err(TransactionError::InstructionError(_, InstructionError::Custom(code)))
+                        if code == TokenError::InsufficientFunds as u32 =>
+                    {
+                        prop_assert_eq!(err.program_error(), Some(&AmountOverflow))
+                    }
+                    // The program panics negating i64::MIN
+                    Err(TransactionError::InstructionError(
+                        _,
+                        InstructionError::ProgramFailedToComplete,
+                    )) => {
+                        prop_assert_eq!(err.program_error(), Some(&AmountOverflow))
+                    }
+                    Err(error) => prop_assert!(false, "{:?} for {:?}", error, err),
+                }
+            }
+        }
+
+        let market_address = get_market_address(&market).unwrap();
+        let market_after =
+            Market::from_bytes(&vm.get_account(&market_address).unwrap().data).unwrap();
+
+        prop_assert_eq!(market_after.sqrt_price_x96, simulator.market.sqrt_price_x96);
+        prop_assert_eq!(
+            get_token_balance(vm, &market_address, &market.token_mint0),
+            simulator.reserve0
+        );
+        prop_assert_eq!(
+            get_token_balance(vm, &market_address, &market.token_mint1),
+            simulator.reserve1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn quote_matches_program_random_swaps() {
+        let vm = RefCell::new(get_vm(vec![ALICE, BOB]));
+        create_tokens(
+            &mut vm.borrow_mut(),
+            [TOKEN_MINT_1],
+            vec![ALICE, BOB],
+            vec![],
+            None,
+        );
+
+        // Every case creates its own config and market, on the same VM
+        let mut runner = TestRunner::new(Config::with_cases(128));
+        runner
+            .run(
+                &(market_settings_input(), prop::collection::vec(swap(), 1..8)),
+                |(input, swaps)| {
+                    let vm = &mut vm.borrow_mut();
+
+                    let Some(mut simulator) = create_market(vm, &input)? else {
+                        return Ok(());
+                    };
+
+                    for swap in swaps {
+                        check_swap(vm, &mut simulator, swap)?;
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn quote_matches_program_amounts_above_i64_max() {
+        let mut vm = get_vm(vec![ALICE, BOB]);
+        create_tokens(&mut vm, [TOKEN_MINT_1], vec![ALICE, BOB], vec![], None);
+
+        let input = MarketSettingsInput {
+            max_supply: 1_000_000_000_000_000,
+            supply_at_graduation: 0,
+            sqrt_price_a_x96: SQRT_PRICE_A,
+            sqrt_price_b_x96: SQRT_PRICE_A * 9 / 8,
+            fee: 0,
+        };
+        let mut simulator = create_market(&mut vm, &input).unwrap().unwrap();
+
+        // Amounts above i64::MAX wrap around and swap the other way
+        for (zero_for_one, exact_in, amount) in [
+            // Exact in of i64::MAX
+            (false, false, i64::MAX as u64 + 2),
+            // Brings reserve 1 above 2^63
+            (false, true, i64::MAX as u64),
+            // Exact out of 2^63
+            (true, true, 1 << 63),
+        ] {
+            check_swap(
+                &mut vm,
+                &mut simulator,
+                SwapCase::Swap {
+                    zero_for_one,
+                    exact_in,
+                    amount,
+                    amount_limit: AmountLimit::None,
+                },
+            )
+            .unwrap();
+        }
+
+        assert!(simulator.reserve1 > 1 << 63);
+    }
+}
@@ -1,10 +1,12 @@
-use anyhow::Result;
 use ruint::aliases::U256;
 
-use crate::quote::math::{mul_div, mul_div_round_up};
-use token_mill_v2_client::errors::TokenMillV2Error::*;
+use crate::{
+    error::Result,
+    quote::math::{mul_div, mul_div_round_up},
+};
+use token_mill_v2_client::errors::TokenMillV2Error::{self, *};
 
-type GetAmountFn = fn(u128, u128, u128, bool) -> Result<u128>;
+type GetAmountFn = fn(u128, u128, u128, bool) -> Result<u128, TokenMillV2Error>;
 
 pub const MAX_FEE_U128: u128 = 1_000_000;
 pub const SQRT_PRICE_SHIFT: usize = 96;
@@ -15,7 +17,7 @@ pub fn get_delta_amounts(
     liquidity: u128,
     delta_amount: i64,
     fee: u32,
-) -> Result<(u128, u64, u64, u64)> {
+) -> Result<(u128, u64, u64, u64), TokenMillV2Error> {
     // Returns the new sqrt price, amount in, amount out and fee amount
     let (new_sqrt_price, amount_in, amount_out, fee_amount): (u128, u64, u64, u64);
 
@@ -40,17 +42,10 @@ pub fn get_delta_amounts(
 
         // If the amount overflows, that means we won't be able to reach the target price
         // `max_amount_in` is set to `u128::MAX` so that it will always be bigger than `amount_in_available`
-        let max_amount_in =
-            get_amount_in(sqrt_price, target_sqrt_price, liquidity, true).or_else(|err| {
-                if err
-                    .downcast_ref::<token_mill_v2_client::errors::TokenMillV2Error>()
-                    .map_or(false, |e| *e == AmountOverflow)
-                {
-                    Ok(u128::MAX)
-                } else {
-                    Err(err)
-                }
-            })?;
+        let max_amount_in = match get_amount_in(sqrt_price, target_sqrt_price, liquidity, true) {
+            Err(AmountOverflow) => u128::MAX,
+            result => result?,
+        };
 
         if max_amount_in > amount_in_available {
             new_sqrt_price = if zero_for_one {
@@ -97,19 +92,17 @@ pub fn get_delta_amounts(
 
         // If the amount overflows, that means we won't be able to reach the target price
         // `max_amount_out` is set to `u128::MAX` so that it will always be bigger than `amount_out_to_fill`
-        let max_amount_out = get_amount_out(sqrt_price, target_sqrt_price, liquidity, false)
-            .or_else(|err| {
-                if err
-                    .downcast_ref::<token_mill_v2_client::errors::TokenMillV2Error>()
-                    .map_or(false, |e| *e == AmountOverflow)
-                {
-                    Ok(u128::MAX)
-                } else {
-                    Err(err)
-                }
-            })?;
+        let max_amount_out = match get_amount_out(sqrt_price, target_sqrt_price, liquidity, false) {
+            Err(AmountOverflow) => u128::MAX,
+            result => result?,
+        };
 
         if max_amount_out > amount_out_to_fill.into() {
+            // The program panics negating `i64::MIN` to get the next sqrt price
+            if delta_amount == i64::MIN {
+                return Err(AmountOverflow);
+            }
+
             new_sqrt_price = if zero_for_one {
                 get_next_sqrt_ratio_from_amount_1(sqrt_price, liquidity, delta_amount)?
             } else {
@@ -142,7 +135,7 @@ pub fn get_amount_0(
     sqrt_price_b: u128,
     liquidity: u128,
     adding: bool,
-) -> Result<u128> {
+) -> Result<u128, TokenMillV2Error> {
     let (sqrt_price_a, sqrt_price_b) = if sqrt_price_a < sqrt_price_b {
         (sqrt_price_a, sqrt_price_b)
     } else {
@@ -175,7 +168,7 @@ pub fn get_amount_1(
     sqrt_price_b: u128,
     liquidity: u128,
     adding: bool,
-) -> Result<u128> {
+) -> Result<u128, TokenMillV2Error> {
     let (sqrt_price_a, sqrt_price_b) = if sqrt_price_a < sqrt_price_b {
         (sqrt_price_a, sqrt_price_b)
     } else {
@@ -190,11 +183,11 @@ pub fn get_amount_1(
         (U256::from(liquidity) * U256::from(sqrt_price_diff))
             .div_ceil(U256::from(2u128.pow(SQRT_PRICE_SHIFT as u32)))
             .try_into()
-            .map_err(|_| AmountOverflow.into())
+            .map_err(|_| AmountOverflow)
     } else {
         ((U256::from(liquidity) * U256::from(sqrt_price_diff)).wrapping_shr(SQRT_PRICE_SHIFT))
             .try_into()
-            .map_err(|_| AmountOverflow.into())
+            .map_err(|_| AmountOverflow)
     }
 }
 
@@ -202,7 +195,7 @@ pub fn get_next_sqrt_ratio_from_amount_0(
     sqrt_price: u128,
     liquidity: u128,
     amount_0: i64,
-) -> Result<u128> {
+) -> Result<u128, TokenMillV2Error> {
     if amount_0 == 0 {
         return Ok(sqrt_price);
     }
@@ -214,7 +207,7 @@ pub fn get_next_sqrt_ratio_from_amount_0(
             .checked_add(U256::from(amount_0) * U256::from(sqrt_price))
             .ok_or(AmountOverflow)?,
         false => liquidity
-            .checked_sub(U256::from(amount_0.abs()) * U256::from(sqrt_price))
+            .checked_sub(U256::from(amount_0.unsigned_abs()) * U256::from(sqrt_price))
             .ok_or(AmountUnderflow)?,
     };
 
@@ -225,14 +218,14 @@ pub fn get_next_sqrt_ratio_from_amount_1(
     sqrt_price: u128,
     liquidity: u128,
     amount_1: i64,
-) -> Result<u128> {
+) -> Result<u128, TokenMillV2Error> {
     let liquidity_x_price = U256::from(sqrt_price) * U256::from(liquidity);
     let numerator = match amount_1.is_positive() {
         true => liquidity_x_price
             .checked_add(U256::from(amount_1).saturating_shl(SQRT_PRICE_SHIFT))
             .ok_or(AmountOverflow)?,
         false => liquidity_x_price
-            .checked_sub(U256::from(amount_1.abs()).saturating_shl(SQRT_PRICE_SHIFT))
+            .checked_sub(U256::from(amount_1.unsigned_abs()).saturating_shl(SQRT_PRICE_SHIFT))
             .ok_or(AmountUnderflow)?,
     };
 
@@ -240,5 +233,22 @@ pub fn get_next_sqrt_ratio_from_amount_1(
         .checked_div(U256::from(liquidity))
         .ok_or(DivisionByZero)?;
 
-    sqrt_price_next.try_into().map_err(|_| PriceOverflow.into())
+    sqrt_price_next.try_into().map_err(|_| PriceOverflow)
+}
+
+// Liquidity needed to sell `amount_0` between the two sqrt prices
+pub fn get_liquidity_from_amount_0(
+    sqrt_price_a: u128,
+    sqrt_price_b: u128,
+    amount_0: u64,
+) -> Result<u128, TokenMillV2Error> {
+    let sqrt_price_diff = sqrt_price_b
+        .checked_sub(sqrt_price_a)
+        .ok_or(InvalidSqrtPrices)?;
+
+    mul_div(
+        U256::from(amount_0) * U256::from(sqrt_price_a),
+        U256::from(sqrt_price_b),
+        U256::from(sqrt_price_diff).saturating_shl(SQRT_PRICE_SHIFT),
+    )
 }
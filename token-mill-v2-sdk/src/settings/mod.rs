@@ -0,0 +1,146 @@
+use crate::{
+    error::Result,
+    quote::{
+        MAX_SQRT_PRICE_X96,
+        swap_math::{MAX_FEE_U128, get_liquidity_from_amount_0},
+    },
+};
+use token_mill_v2_client::{
+    errors::TokenMillV2Error::{AmountUnderflow, InvalidFee, InvalidSqrtPrices},
+    types::{MarketSettings, MarketSettingsInput},
+};
+
+/// Derives the market settings the program stores for `input`, with the same validation
+/// Pool A sells `supply_at_graduation` up to `sqrt_price_b_x96`, pool B sells the rest up to `MAX_SQRT_PRICE_X96`
+pub fn get_market_settings(input: &MarketSettingsInput) -> Result<MarketSettings> {
+    let liquidity_a = get_liquidity_from_amount_0(
+        input.sqrt_price_a_x96,
+        input.sqrt_price_b_x96,
+        input.supply_at_graduation,
+    )?;
+
+    let supply_pool_b = input
+        .max_supply
+        .checked_sub(input.supply_at_graduation)
+        .ok_or(AmountUnderflow)?;
+
+    if input.sqrt_price_b_x96 > MAX_SQRT_PRICE_X96 {
+        return Err(InvalidSqrtPrices.into());
+    }
+
+    let liquidity_b =
+        get_liquidity_from_amount_0(input.sqrt_price_b_x96, MAX_SQRT_PRICE_X96, supply_pool_b)?;
+
+    if u128::from(input.fee) >= MAX_FEE_U128 {
+        return Err(InvalidFee.into());
+    }
+
+    Ok(MarketSettings {
+        max_supply: input.max_supply,
+        sqrt_price_a_x96: input.sqrt_price_a_x96,
+        sqrt_price_b_x96: input.sqrt_price_b_x96,
+        liquidity_a,
+        liquidity_b,
+        fee: input.fee,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use litesvm::LiteSVM;
+    use token_mill_v2_client::{accounts::TokenMillConfig, errors::TokenMillV2Error};
+
+    use crate::test_utils::{
+        constants::*,
+        instructions::{get_create_config_ix_builder, parse_error},
+        test_vm::{create_tokens, execute_instructions, get_vm, make_address},
+    };
+
+    use super::*;
+
+    fn create_config(
+        vm: &mut LiteSVM,
+        index: usize,
+        input: &MarketSettingsInput,
+    ) -> std::result::Result<MarketSettings, TokenMillV2Error> {
+        let config = make_address(&format!("config_{}", index));
+
+        let mut create_config_builder = get_create_config_ix_builder();
+        create_config_builder
+            .token_mill_config(config)
+            .market_settings(input.clone());
+
+        let instructions = vec![create_config_builder.instruction()];
+        let result = execute_instructions(vm, instructions.clone(), &ALICE);
+
+        if result.is_err() {
+            return Err(parse_error(&instructions, result).unwrap());
+        }
+
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&config).unwrap().data).unwrap();
+
+        Ok(config.default_market_settings)
+    }
+
+    #[test]
+    fn market_settings_match_program() {
+        let mut vm = get_vm(vec![ALICE, BOB]);
+        create_tokens(&mut vm, [TOKEN_MINT_1], vec![ALICE, BOB], vec![], None);
+
+        let input = MarketSettingsInput {
+            max_supply: MAX_SUPPLY,
+            supply_at_graduation: SUPPLY_AT_GRADUATION,
+            sqrt_price_a_x96: SQRT_PRICE_A,
+            sqrt_price_b_x96: SQRT_PRICE_B,
+            fee: FEE,
+        };
+
+        let inputs = [
+            input.clone(),
+            MarketSettingsInput {
+                sqrt_price_a_x96: 0,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                supply_at_graduation: MAX_SUPPLY,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                max_supply: u64::MAX,
+                supply_at_graduation: u64::MAX / 2,
+                sqrt_price_a_x96: 1 << 60,
+                sqrt_price_b_x96: 1 << 126,
+                ..input.clone()
+            },
+            // Invalid inputs
+            MarketSettingsInput {
+                sqrt_price_a_x96: SQRT_PRICE_B + 1,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                sqrt_price_a_x96: SQRT_PRICE_B,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                sqrt_price_b_x96: MAX_SQRT_PRICE_X96,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                sqrt_price_b_x96: MAX_SQRT_PRICE_X96 - 1,
+                ..input.clone()
+            },
+            MarketSettingsInput {
+                fee: MAX_FEE_U128 as u32,
+                ..input.clone()
+            },
+        ];
+
+        for (index, input) in inputs.iter().enumerate() {
+            let expected = create_config(&mut vm, index, input);
+            let settings =
+                get_market_settings(input).map_err(|err| err.program_error().unwrap().clone());
+
+            assert_eq!(settings, expected);
+        }
+    }
+}
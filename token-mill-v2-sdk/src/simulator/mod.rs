@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::Result,
+    quote::{FeeSplit, MAX_SQRT_PRICE_X96, Quote, get_fee_split, quote},
+    slippage::get_max_sqrt_price_limit,
+};
+use token_mill_v2_client::{
+    accounts::{Market, TokenMillConfig},
+    errors::TokenMillV2Error::{
+        AmountOverflow, AmountUnderflow, InvalidSqrtPriceLimit, SlippageExceeded, ZeroDeltaAmount,
+    },
+    types::SwapParameters,
+};
+
+/// Market state applying swaps as the program does, without running it
+/// Swaps either succeed and update the state, or fail with the program error and leave it unchanged
+#[derive(Debug, Clone)]
+pub struct MarketSimulator {
+    pub market: Market,
+    pub config: TokenMillConfig,
+    /// Balances of the market reserve token accounts
+    pub reserve0: u64,
+    pub reserve1: u64,
+    /// Token 1 fees received by each fee reserve since the simulator was created
+    /// Totals aren't token account balances, and can exceed a u64 over long simulations
+    pub fee_balances: HashMap<Pubkey, u128>,
+}
+
+impl MarketSimulator {
+    pub fn new(market: Market, config: TokenMillConfig, reserve0: u64, reserve1: u64) -> Self {
+        Self {
+            market,
+            config,
+            reserve0,
+            reserve1,
+            fee_balances: HashMap::new(),
+        }
+    }
+
+    pub fn get_fee_balance(&self, fee_reserve: &Pubkey) -> u128 {
+        self.fee_balances.get(fee_reserve).copied().unwrap_or(0)
+    }
+
+    /// Applies the `swap` instruction, up to the curve bounds
+    /// As in the program, amounts are cast to `i64`, so amounts above `i64::MAX` wrap around and
+    /// swap the other way, e.g. `BuyExactIn(u64::MAX, _)` buys exactly 1 token 0, still checking the amount out
+    pub fn swap(&mut self, swap_parameters: &SwapParameters) -> Result<(Quote, FeeSplit)> {
+        let (zero_for_one, exact_in, amount, amount_limit) = match *swap_parameters {
+            SwapParameters::BuyExactIn(amount_in, min_amount_out) => {
+                (false, true, amount_in, min_amount_out)
+            }
+            SwapParameters::BuyExactOut(max_amount_in, amount_out) => {
+                (false, false, amount_out, max_amount_in)
+            }
+            SwapParameters::SellExactIn(amount_in, min_amount_out) => {
+                (true, true, amount_in, min_amount_out)
+            }
+            SwapParameters::SellExactOut(max_amount_in, amount_out) => {
+                (true, false, amount_out, max_amount_in)
+            }
+        };
+
+        let amount = amount as i64;
+        // The program panics negating `i64::MIN`
+        let delta_amount = if exact_in {
+            amount
+        } else {
+            amount.checked_neg().ok_or(AmountOverflow)?
+        };
+
+        if delta_amount == 0 {
+            return Err(ZeroDeltaAmount.into());
+        }
+
+        let sqrt_price_limit = get_max_sqrt_price_limit(&self.market, zero_for_one);
+        let result = quote(&self.market, zero_for_one, delta_amount, sqrt_price_limit)?;
+
+        if (exact_in && result.amount_out < amount_limit)
+            || (!exact_in && result.amount_in > amount_limit)
+        {
+            return Err(SlippageExceeded.into());
+        }
+
+        self.apply(result)
+    }
+
+    /// Applies the `swap_with_price_limit` instruction
+    /// The limit must be past the current price in the swap direction, and within the curve bounds
+    pub fn swap_with_price_limit(
+        &mut self,
+        zero_for_one: bool,
+        delta_amount: i64,
+        sqrt_price_limit: u128,
+    ) -> Result<(Quote, FeeSplit)> {
+        let sqrt_price = self.market.sqrt_price_x96;
+        let is_valid_limit = if zero_for_one {
+            sqrt_price_limit < sqrt_price
+                && sqrt_price_limit >= self.market.settings.sqrt_price_a_x96
+        } else {
+            sqrt_price_limit > sqrt_price && sqrt_price_limit <= MAX_SQRT_PRICE_X96
+        };
+
+        if !is_valid_limit {
+            return Err(InvalidSqrtPriceLimit.into());
+        }
+
+        if delta_amount == 0 {
+            return Err(ZeroDeltaAmount.into());
+        }
+
+        let result = quote(&self.market, zero_for_one, delta_amount, sqrt_price_limit)?;
+
+        self.apply(result)
+    }
+
+    /// Buys send the fee out of the amount in, sells swap the token 0 fee and send it out of reserve 1
+    fn apply(&mut self, result: Quote) -> Result<(Quote, FeeSplit)> {
+        let fee_split = get_fee_split(&self.market, &self.config, result.fee_amount_token_1)?;
+
+        let (reserve0, reserve1) = if result.zero_for_one {
+            (
+                self.reserve0
+                    .checked_add(result.amount_in)
+                    .ok_or(AmountOverflow)?,
+                self.reserve1
+                    .checked_sub(result.amount_out)
+                    .and_then(|reserve1| reserve1.checked_sub(result.fee_amount_token_1))
+                    .ok_or(AmountUnderflow)?,
+            )
+        } else {
+            (
+                self.reserve0
+                    .checked_sub(result.amount_out)
+                    .ok_or(AmountUnderflow)?,
+                self.reserve1
+                    .checked_add(result.amount_in - result.fee_amount_token_in)
+                    .ok_or(AmountOverflow)?,
+            )
+        };
+
+        self.reserve0 = reserve0;
+        self.reserve1 = reserve1;
+        self.market.sqrt_price_x96 = result.next_sqrt_price;
+
+        // The creator fee reserve can be the protocol one
+        *self
+            .fee_balances
+            .entry(fee_split.protocol_fee_reserve)
+            .or_default() += u128::from(fee_split.protocol_fee_amount);
+        *self
+            .fee_balances
+            .entry(fee_split.creator_fee_reserve)
+            .or_default() += u128::from(fee_split.creator_fee_amount);
+
+        Ok((result, fee_split))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use token_mill_v2_client::errors::TokenMillV2Error;
+
+    use crate::test_utils::{
+        constants::{
+            ALICE, BOB, CONFIG, MARKET, SQRT_PRICE_A, SQRT_PRICE_B, TOKEN_MINT_0, TOKEN_MINT_1,
+        },
+        instructions::{
+            get_swap_ix_builder, get_swap_with_price_limit_ix_builder, get_vm_and_create_market,
+            parse_error,
+        },
+        test_vm::{execute_instructions, get_ata, get_token_balance},
+    };
+
+    use super::*;
+
+    #[test]
+    fn simulator_matches_program() {
+        let mut vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+        let fee_reserve = get_ata(&BOB, &TOKEN_MINT_1);
+        let fee_balance_before = get_token_balance(&vm, &BOB, &TOKEN_MINT_1);
+
+        let mut simulator = MarketSimulator::new(
+            market,
+            config,
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_0),
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_1),
+        );
+
+        let swaps = [
+            SwapParameters::BuyExactIn(1_000_000_000, 0),
+            SwapParameters::BuyExactOut(u64::MAX >> 1, 1_000_000_000_000),
+            SwapParameters::SellExactIn(1_000_000_000_000, 1),
+            SwapParameters::SellExactOut(u64::MAX >> 1, 1_000_000),
+            SwapParameters::BuyExactIn(1, 0),
+            // Amounts above i64::MAX wrap around, to an exact out of 1 and an exact in of 1
+            SwapParameters::BuyExactIn(u64::MAX, 1),
+            SwapParameters::SellExactOut(u64::MAX >> 1, u64::MAX),
+        ];
+
+        for swap_parameters in swaps {
+            simulator.swap(&swap_parameters).unwrap();
+
+            let mut swap_builder = get_swap_ix_builder();
+            swap_builder.swap_parameters(swap_parameters);
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+        }
+
+        // Buy across the pool A/B boundary with a partial fill, then sell back into pool A
+        for (zero_for_one, delta_amount, sqrt_price_limit) in [
+            (false, i64::MAX, SQRT_PRICE_B + SQRT_PRICE_A),
+            (true, -1_000_000, SQRT_PRICE_A),
+            (true, i64::MAX, SQRT_PRICE_B - SQRT_PRICE_A),
+        ] {
+            vm.expire_blockhash();
+
+            simulator
+                .swap_with_price_limit(zero_for_one, delta_amount, sqrt_price_limit)
+                .unwrap();
+
+            let mut swap_builder = get_swap_with_price_limit_ix_builder();
+            swap_builder
+                .zero_for_one(zero_for_one)
+                .delta_amount(delta_amount)
+                .sqrt_price_limit_x96(sqrt_price_limit);
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+        }
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+
+        assert_eq!(simulator.market.sqrt_price_x96, market.sqrt_price_x96);
+        assert_eq!(
+            simulator.reserve0,
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_0)
+        );
+        assert_eq!(
+            simulator.reserve1,
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_1)
+        );
+        assert_eq!(simulator.fee_balances.len(), 1);
+        assert_eq!(
+            simulator.get_fee_balance(&fee_reserve),
+            u128::from(get_token_balance(&vm, &BOB, &TOKEN_MINT_1) - fee_balance_before)
+        );
+    }
+
+    #[test]
+    fn simulator_errors_match_program() {
+        let mut vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+        let mut simulator = MarketSimulator::new(
+            market,
+            config,
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_0),
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_1),
+        );
+
+        let swap_parameters = SwapParameters::BuyExactIn(1_000_000_000, 0);
+        let (result, _) = simulator.clone().swap(&swap_parameters).unwrap();
+
+        for (swap_parameters, expected) in [
+            (
+                SwapParameters::BuyExactIn(0, 0),
+                TokenMillV2Error::ZeroDeltaAmount,
+            ),
+            (
+                SwapParameters::BuyExactIn(1_000_000_000, result.amount_out + 1),
+                TokenMillV2Error::SlippageExceeded,
+            ),
+            (
+                SwapParameters::BuyExactOut(result.amount_in - 1, result.amount_out),
+                TokenMillV2Error::SlippageExceeded,
+            ),
+            // Exact out of 1, the amount out is still checked against the second amount
+            (
+                SwapParameters::BuyExactIn(u64::MAX, 2),
+                TokenMillV2Error::SlippageExceeded,
+            ),
+            // Exact out of 2^63
+            (
+                SwapParameters::BuyExactIn(1 << 63, 0),
+                TokenMillV2Error::AmountInOverflow,
+            ),
+        ] {
+            let err = simulator.swap(&swap_parameters).unwrap_err();
+            assert_eq!(err.program_error(), Some(&expected));
+
+            let mut swap_builder = get_swap_ix_builder();
+            swap_builder.swap_parameters(swap_parameters);
+            let instructions = vec![swap_builder.instruction()];
+            let error = parse_error(
+                &instructions,
+                execute_instructions(&mut vm, instructions.clone(), &ALICE),
+            )
+            .unwrap();
+            assert_eq!(error, expected);
+        }
+
+        for (zero_for_one, delta_amount, sqrt_price_limit, expected) in [
+            (false, 0, SQRT_PRICE_B, TokenMillV2Error::ZeroDeltaAmount),
+            // The price limit is checked before the amount
+            (
+                true,
+                0,
+                SQRT_PRICE_A,
+                TokenMillV2Error::InvalidSqrtPriceLimit,
+            ),
+            (
+                false,
+                1_000,
+                SQRT_PRICE_A,
+                TokenMillV2Error::InvalidSqrtPriceLimit,
+            ),
+            (
+                false,
+                1_000,
+                MAX_SQRT_PRICE_X96 + 1,
+                TokenMillV2Error::InvalidSqrtPriceLimit,
+            ),
+            (
+                true,
+                1_000,
+                SQRT_PRICE_A,
+                TokenMillV2Error::InvalidSqrtPriceLimit,
+            ),
+        ] {
+            let err = simulator
+                .swap_with_price_limit(zero_for_one, delta_amount, sqrt_price_limit)
+                .unwrap_err();
+            assert_eq!(err.program_error(), Some(&expected));
+
+            let mut swap_builder = get_swap_with_price_limit_ix_builder();
+            swap_builder
+                .zero_for_one(zero_for_one)
+                .delta_amount(delta_amount)
+                .sqrt_price_limit_x96(sqrt_price_limit);
+            let instructions = vec![swap_builder.instruction()];
+            let error = parse_error(
+                &instructions,
+                execute_instructions(&mut vm, instructions.clone(), &ALICE),
+            )
+            .unwrap();
+            assert_eq!(error, expected);
+        }
+
+        // Failed swaps leave the state unchanged
+        assert_eq!(simulator.market.sqrt_price_x96, SQRT_PRICE_A);
+        assert!(simulator.fee_balances.is_empty());
+    }
+
+    #[test]
+    fn simulator_fee_balances_exceed_u64() {
+        let vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let config = TokenMillConfig::from_bytes(&vm.get_account(&CONFIG).unwrap().data).unwrap();
+        let fee_reserve = get_ata(&BOB, &TOKEN_MINT_1);
+        let mut simulator = MarketSimulator::new(
+            market,
+            config,
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_0),
+            get_token_balance(&vm, &MARKET, &TOKEN_MINT_1),
+        );
+        simulator
+            .fee_balances
+            .insert(fee_reserve, u128::from(u64::MAX));
+
+        let (_, fee_split) = simulator
+            .swap(&SwapParameters::BuyExactIn(1_000_000_000, 0))
+            .unwrap();
+
+        assert_eq!(
+            simulator.get_fee_balance(&fee_reserve),
+            u128::from(u64::MAX)
+                + u128::from(fee_split.protocol_fee_amount)
+                + u128::from(fee_split.creator_fee_amount)
+        );
+    }
+}
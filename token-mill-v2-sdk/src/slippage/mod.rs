@@ -0,0 +1,242 @@
+use ruint::aliases::U512;
+
+use crate::{
+    error::Result,
+    quote::{MAX_SQRT_PRICE_X96, Quote},
+};
+use token_mill_v2_client::{
+    accounts::Market,
+    errors::TokenMillV2Error::{AmountOverflow, InvalidSqrtPriceLimit},
+    types::SwapParameters,
+};
+
+pub const BPS_PRECISION: u32 = 10_000;
+
+/// Furthest sqrt price limit accepted by the program, for swaps without price protection
+pub fn get_max_sqrt_price_limit(market: &Market, zero_for_one: bool) -> u128 {
+    if zero_for_one {
+        market.settings.sqrt_price_a_x96
+    } else {
+        MAX_SQRT_PRICE_X96
+    }
+}
+
+/// Sqrt price limit allowing the price to move by `slippage_bps` from the current price
+/// The limit is clamped to the curve bounds, and always at least one unit away from the current price
+pub fn get_sqrt_price_limit(
+    market: &Market,
+    zero_for_one: bool,
+    slippage_bps: u32,
+) -> Result<u128> {
+    let sqrt_price = market.sqrt_price_x96;
+    let max_sqrt_price_limit = get_max_sqrt_price_limit(market, zero_for_one);
+
+    if zero_for_one {
+        if sqrt_price <= max_sqrt_price_limit {
+            return Err(InvalidSqrtPriceLimit.into());
+        }
+
+        if slippage_bps >= BPS_PRECISION {
+            return Ok(max_sqrt_price_limit);
+        }
+
+        // Rounded up, to stay within the slippage
+        let price_x_bps =
+            U512::from(sqrt_price).pow(U512::from(2)) * U512::from(BPS_PRECISION - slippage_bps);
+        let mut sqrt_price_limit = (price_x_bps / U512::from(BPS_PRECISION)).root(2);
+        if sqrt_price_limit.pow(U512::from(2)) * U512::from(BPS_PRECISION) < price_x_bps {
+            sqrt_price_limit += U512::from(1);
+        }
+
+        // Safe cast as sqrt_price_limit <= sqrt_price
+        let sqrt_price_limit = sqrt_price_limit.to::<u128>();
+
+        Ok(sqrt_price_limit
+            .min(sqrt_price - 1)
+            .max(max_sqrt_price_limit))
+    } else {
+        if sqrt_price >= max_sqrt_price_limit {
+            return Err(InvalidSqrtPriceLimit.into());
+        }
+
+        // Rounded down, to stay within the slippage
+        let sqrt_price_limit = (U512::from(sqrt_price).pow(U512::from(2))
+            * U512::from(u64::from(BPS_PRECISION) + u64::from(slippage_bps))
+            / U512::from(BPS_PRECISION))
+        .root(2);
+
+        let sqrt_price_limit = u128::try_from(sqrt_price_limit).unwrap_or(max_sqrt_price_limit);
+
+        Ok(sqrt_price_limit
+            .max(sqrt_price + 1)
+            .min(max_sqrt_price_limit))
+    }
+}
+
+/// Swap parameters executing `quote`, with the other amount bounded by `slippage_bps`
+/// Exact in swaps get a minimum amount out, exact out swaps a maximum amount in, both rounded in favor of the user
+/// All variants hold the amount in first: `(amount_in, min_amount_out)` or `(max_amount_in, amount_out)`
+pub fn get_swap_parameters(quote: &Quote, slippage_bps: u32) -> Result<SwapParameters> {
+    let swap_parameters = if quote.exact_in {
+        let min_amount_out = (u128::from(quote.amount_out)
+            * u128::from(BPS_PRECISION.saturating_sub(slippage_bps)))
+        .div_ceil(u128::from(BPS_PRECISION));
+
+        // Safe cast as min_amount_out <= amount_out
+        let min_amount_out = min_amount_out as u64;
+
+        if quote.zero_for_one {
+            SwapParameters::SellExactIn(quote.amount_in, min_amount_out)
+        } else {
+            SwapParameters::BuyExactIn(quote.amount_in, min_amount_out)
+        }
+    } else {
+        let max_amount_in = (u128::from(quote.amount_in)
+            * (u128::from(BPS_PRECISION) + u128::from(slippage_bps))
+            / u128::from(BPS_PRECISION))
+        .try_into()
+        .map_err(|_| AmountOverflow)?;
+
+        if quote.zero_for_one {
+            SwapParameters::SellExactOut(max_amount_in, quote.amount_out)
+        } else {
+            SwapParameters::BuyExactOut(max_amount_in, quote.amount_out)
+        }
+    };
+
+    Ok(swap_parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use token_mill_v2_client::errors::TokenMillV2Error;
+
+    use crate::{
+        pricing::get_price,
+        quote::quote,
+        test_utils::{
+            constants::{ALICE, MARKET, SQRT_PRICE_A},
+            instructions::{
+                get_swap_ix_builder, get_swap_with_price_limit_ix_builder,
+                get_vm_and_create_market, parse_error,
+            },
+            test_vm::execute_instructions,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn sqrt_price_limit() {
+        let mut vm = get_vm_and_create_market();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        assert!(get_sqrt_price_limit(&market, true, 100).is_err());
+
+        let mut swap_builder = get_swap_ix_builder();
+        swap_builder.swap_parameters(SwapParameters::BuyExactIn(10_000_000_000, 0));
+        execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+
+        let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+        let price = get_price(market.sqrt_price_x96, 0, 0);
+
+        let buy_limit = get_sqrt_price_limit(&market, false, 100).unwrap();
+        let sell_limit = get_sqrt_price_limit(&market, true, 100).unwrap();
+
+        assert!((get_price(buy_limit, 0, 0) / price - 1.01).abs() < 1e-9);
+        assert!((get_price(sell_limit, 0, 0) / price - 0.99).abs() < 1e-9);
+
+        assert_eq!(
+            get_sqrt_price_limit(&market, false, 0).unwrap(),
+            market.sqrt_price_x96 + 1
+        );
+        assert_eq!(
+            get_sqrt_price_limit(&market, true, 0).unwrap(),
+            market.sqrt_price_x96 - 1
+        );
+        let market_near_max = Market {
+            sqrt_price_x96: MAX_SQRT_PRICE_X96 - 1_000,
+            ..market.clone()
+        };
+        assert_eq!(
+            get_sqrt_price_limit(&market_near_max, false, 100).unwrap(),
+            MAX_SQRT_PRICE_X96
+        );
+        assert_eq!(
+            get_sqrt_price_limit(&market, true, BPS_PRECISION).unwrap(),
+            SQRT_PRICE_A
+        );
+
+        // The program accepts all the limits
+        for (zero_for_one, slippage_bps) in [
+            (false, 0),
+            (false, 100),
+            (false, u32::MAX),
+            (true, 0),
+            (true, 100),
+            (true, BPS_PRECISION),
+        ] {
+            vm.expire_blockhash();
+
+            let mut swap_builder = get_swap_with_price_limit_ix_builder();
+            swap_builder
+                .zero_for_one(zero_for_one)
+                .delta_amount(1_000)
+                .sqrt_price_limit_x96(
+                    get_sqrt_price_limit(&market, zero_for_one, slippage_bps).unwrap(),
+                );
+
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+        }
+    }
+
+    #[test]
+    fn swap_parameters() {
+        let mut vm = get_vm_and_create_market();
+
+        for (zero_for_one, delta_amount) in [
+            (false, 1_000_000_000),
+            (false, -1_000_000_000_000),
+            (true, 1_000_000_000),
+            (true, -1_000_000),
+        ] {
+            let market = Market::from_bytes(&vm.get_account(&MARKET).unwrap().data).unwrap();
+            let sqrt_price_limit = get_max_sqrt_price_limit(&market, zero_for_one);
+
+            let mut result = quote(&market, zero_for_one, delta_amount, sqrt_price_limit).unwrap();
+            assert_eq!(result.exact_in, delta_amount > 0);
+
+            let swap_parameters = get_swap_parameters(&result, 100).unwrap();
+            match swap_parameters {
+                SwapParameters::BuyExactIn(_, min_amount_out)
+                | SwapParameters::SellExactIn(_, min_amount_out) => {
+                    assert_eq!(min_amount_out, (result.amount_out * 99).div_ceil(100))
+                }
+                SwapParameters::BuyExactOut(max_amount_in, _)
+                | SwapParameters::SellExactOut(max_amount_in, _) => {
+                    assert_eq!(max_amount_in, result.amount_in * 101 / 100)
+                }
+            }
+
+            // A quote worse than the actual swap by more than the slippage is rejected
+            if result.exact_in {
+                result.amount_out = result.amount_out * 102 / 100;
+            } else {
+                result.amount_in = result.amount_in * 98 / 100;
+            }
+
+            let mut swap_builder = get_swap_ix_builder();
+            swap_builder.swap_parameters(get_swap_parameters(&result, 100).unwrap());
+            let instructions = vec![swap_builder.instruction()];
+            let error = parse_error(
+                &instructions,
+                execute_instructions(&mut vm, instructions.clone(), &ALICE),
+            )
+            .unwrap();
+            assert_eq!(error, TokenMillV2Error::SlippageExceeded);
+
+            swap_builder.swap_parameters(swap_parameters);
+            execute_instructions(&mut vm, vec![swap_builder.instruction()], &ALICE).unwrap();
+        }
+    }
+}
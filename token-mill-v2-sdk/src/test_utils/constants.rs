@@ -9,7 +9,7 @@ pub const TOKEN_MINT_1: Pubkey = pubkey!("G1RFVzDxJRSeYHE1KXExLVw1Avo7kaKwtLjLXL
 pub const CONFIG: Pubkey = pubkey!("D6qYhV5juiHXsqCa72iaKJWs2nAexJPnFA59764rAu93");
 pub const MARKET: Pubkey = pubkey!("Hbb63RfKy5Dpba5W4xNkRcM1G67jaHpzHmvQe2Bck2Lq"); // PDA from TOKEN_MINT_0
 
-pub const METADATA_PROGRAM: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+pub const METADATA_PROGRAM: Pubkey = crate::instructions::METADATA_PROGRAM_ID;
 
 pub const PROTOCOL_FEE_SHARE: u32 = 400_000; // 40%
 pub const FEE_UPDATE_COOLDOWN: u32 = 3_600; // 1 hour
@@ -2,11 +2,21 @@ use litesvm::{
     LiteSVM,
     types::{FailedTransactionMetadata, TransactionMetadata},
 };
-use num_traits::FromPrimitive;
-use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError};
-use token_mill_v2_client::{errors::TokenMillV2Error, instructions::*, types::MarketSettingsInput};
+use solana_sdk::{instruction::Instruction, transaction::TransactionError};
+use token_mill_v2_client::{
+    accounts::{MARKET_DISCRIMINATOR, Market, TOKEN_MILL_CONFIG_DISCRIMINATOR, TokenMillConfig},
+    errors::TokenMillV2Error,
+    instructions::*,
+    types::MarketSettingsInput,
+};
 
 use super::{constants::*, test_vm::*};
+use crate::{
+    instructions::{
+        CreateMarketAccounts, create_market_ix, get_associated_token_address, get_market_address,
+    },
+    settings::get_market_settings,
+};
 
 pub fn get_vm_and_create_market() -> LiteSVM {
     let mut svm = get_vm(vec![ALICE, BOB]);
@@ -17,7 +27,7 @@ pub fn get_vm_and_create_market() -> LiteSVM {
         &mut svm,
         vec![
             get_create_config_ix_builder().instruction(),
-            get_market_creation_ix_builder().instruction(),
+            get_market_creation_ix(),
         ],
         &ALICE,
     )
@@ -28,17 +38,14 @@ pub fn get_vm_and_create_market() -> LiteSVM {
     svm
 }
 
+/// Decodes the error of a transaction that failed to execute `instructions`
 pub fn parse_error(
+    instructions: &[Instruction],
     result: Result<TransactionMetadata, FailedTransactionMetadata>,
 ) -> Result<TokenMillV2Error, TransactionError> {
     let result = result.unwrap_err();
 
-    match result.err {
-        TransactionError::InstructionError(_, InstructionError::Custom(error_code)) => {
-            Ok(TokenMillV2Error::from_u32(error_code).unwrap())
-        }
-        _ => Err(result.err),
-    }
+    TokenMillV2Error::from_transaction_error(&result.err, instructions).ok_or(result.err)
 }
 
 pub fn get_create_config_ix_builder() -> CreateConfigBuilder {
@@ -48,82 +55,116 @@ pub fn get_create_config_ix_builder() -> CreateConfigBuilder {
         .token_mill_config(CONFIG)
         .quote_token_mint(TOKEN_MINT_1)
         .protocol_fee_share(PROTOCOL_FEE_SHARE)
-        .protocol_fee_token_account(get_ata(&BOB, &TOKEN_MINT_1))
-        .kotm_fee_token_account(get_ata(&BOB, &TOKEN_MINT_1))
+        .protocol_fee_token_account(get_associated_token_address(&BOB, &TOKEN_MINT_1))
+        .kotm_fee_token_account(get_associated_token_address(&BOB, &TOKEN_MINT_1))
         .fee_recipient_change_cooldown(FEE_UPDATE_COOLDOWN)
-        .market_settings(MarketSettingsInput {
-            max_supply: MAX_SUPPLY,
-            supply_at_graduation: SUPPLY_AT_GRADUATION,
-            sqrt_price_a_x96: SQRT_PRICE_A,
-            sqrt_price_b_x96: SQRT_PRICE_B,
-            fee: FEE,
-        })
+        .market_settings(get_market_settings_input())
         .admin(ALICE);
 
     create_config_builder
 }
 
-pub fn get_market_creation_ix_builder() -> CreateMarketBuilder {
-    let mut create_market_builder = CreateMarketBuilder::new();
-
-    let token_mint_0_metadata = Pubkey::find_program_address(
-        &[
-            "metadata".as_bytes(),
-            &METADATA_PROGRAM.to_bytes(),
-            &TOKEN_MINT_0.to_bytes(),
-        ],
-        &METADATA_PROGRAM,
-    )
-    .0;
-
-    create_market_builder
-        .token_mill_config(CONFIG)
-        .name("Test Market".to_string())
-        .uri("uri.url".to_string())
-        .symbol("TEST".to_string())
-        .token_mint0(TOKEN_MINT_0)
-        .token0_metadata(token_mint_0_metadata)
-        .token_mint1(TOKEN_MINT_1)
-        .market(MARKET)
-        .market_reserve0(get_ata(&MARKET, &TOKEN_MINT_0))
-        .market_reserve1(get_ata(&MARKET, &TOKEN_MINT_1))
-        .creator(ALICE);
-
-    create_market_builder
+pub fn get_market_creation_ix() -> Instruction {
+    create_test_market_ix().0
 }
 
+/// Swap builder for ALICE on the market created by `get_market_creation_ix`
 pub fn get_swap_ix_builder() -> SwapBuilder {
-    let mut swap_builder = SwapBuilder::new();
+    let market = get_market();
+    let config = get_config();
+
+    let mut swap_builder = crate::instructions::get_swap_ix_builder(
+        &get_market_address(&market).unwrap(),
+        &market,
+        &config.protocol_fee_reserve,
+        &config.creator_fee_pool,
+    );
 
     swap_builder
-        .config(CONFIG)
-        .market(MARKET)
-        .market_reserve0(get_ata(&MARKET, &TOKEN_MINT_0))
-        .market_reserve1(get_ata(&MARKET, &TOKEN_MINT_1))
-        .fee_reserve(get_ata(&BOB, &TOKEN_MINT_1))
-        .protocol_fee_reserve(get_ata(&BOB, &TOKEN_MINT_1))
-        .creator_fee_pool(get_ata(&BOB, &TOKEN_MINT_1))
-        .user_token_account0(get_ata(&ALICE, &TOKEN_MINT_0))
-        .user_token_account1(get_ata(&ALICE, &TOKEN_MINT_1))
+        .user_token_account0(get_associated_token_address(&ALICE, &TOKEN_MINT_0))
+        .user_token_account1(get_associated_token_address(&ALICE, &TOKEN_MINT_1))
         .user(ALICE);
 
     swap_builder
 }
 
+/// Same as `get_swap_ix_builder`, for `swap_with_price_limit`
 pub fn get_swap_with_price_limit_ix_builder() -> SwapWithPriceLimitBuilder {
-    let mut swap_with_price_limit_builder = SwapWithPriceLimitBuilder::new();
+    let market = get_market();
+    let config = get_config();
+
+    let mut swap_with_price_limit_builder =
+        crate::instructions::get_swap_with_price_limit_ix_builder(
+            &get_market_address(&market).unwrap(),
+            &market,
+            &config.protocol_fee_reserve,
+            &config.creator_fee_pool,
+        );
 
     swap_with_price_limit_builder
-        .config(CONFIG)
-        .market(MARKET)
-        .market_reserve0(get_ata(&MARKET, &TOKEN_MINT_0))
-        .market_reserve1(get_ata(&MARKET, &TOKEN_MINT_1))
-        .fee_reserve(get_ata(&BOB, &TOKEN_MINT_1))
-        .protocol_fee_reserve(get_ata(&BOB, &TOKEN_MINT_1))
-        .creator_fee_pool(get_ata(&BOB, &TOKEN_MINT_1))
-        .user_token_account0(get_ata(&ALICE, &TOKEN_MINT_0))
-        .user_token_account1(get_ata(&ALICE, &TOKEN_MINT_1))
+        .user_token_account0(get_associated_token_address(&ALICE, &TOKEN_MINT_0))
+        .user_token_account1(get_associated_token_address(&ALICE, &TOKEN_MINT_1))
         .user(ALICE);
 
     swap_with_price_limit_builder
 }
+
+/// Config account state after `get_create_config_ix_builder`
+pub fn get_config() -> TokenMillConfig {
+    TokenMillConfig {
+        discriminator: TOKEN_MILL_CONFIG_DISCRIMINATOR,
+        admin: ALICE,
+        quote_token_mint: TOKEN_MINT_1,
+        protocol_fee_share: PROTOCOL_FEE_SHARE,
+        protocol_fee_reserve: get_associated_token_address(&BOB, &TOKEN_MINT_1),
+        creator_fee_pool: get_associated_token_address(&BOB, &TOKEN_MINT_1),
+        fee_recipient_change_cooldown: FEE_UPDATE_COOLDOWN,
+        default_market_settings: get_market_settings(&get_market_settings_input()).unwrap(),
+    }
+}
+
+/// Market account state after `get_market_creation_ix`
+pub fn get_market() -> Market {
+    let config = get_config();
+    let accounts = create_test_market_ix().1;
+
+    Market {
+        discriminator: MARKET_DISCRIMINATOR,
+        config: CONFIG,
+        creator: ALICE,
+        swap_authority: None,
+        token_mint0: TOKEN_MINT_0,
+        token_mint1: config.quote_token_mint,
+        reserve0: accounts.market_reserve0,
+        reserve1: accounts.market_reserve1,
+        fee_reserve: None,
+        fee_reserve_last_update: 0,
+        settings: config.default_market_settings,
+        sqrt_price_x96: SQRT_PRICE_A,
+        bump: [Market::find_pda(&TOKEN_MINT_0).1],
+    }
+}
+
+fn create_test_market_ix() -> (Instruction, CreateMarketAccounts) {
+    create_market_ix(
+        &CONFIG,
+        &get_config(),
+        &ALICE,
+        &TOKEN_MINT_0,
+        "Test Market",
+        "TEST",
+        "uri.url",
+        None,
+    )
+    .unwrap()
+}
+
+fn get_market_settings_input() -> MarketSettingsInput {
+    MarketSettingsInput {
+        max_supply: MAX_SUPPLY,
+        supply_at_graduation: SUPPLY_AT_GRADUATION,
+        sqrt_price_a_x96: SQRT_PRICE_A,
+        sqrt_price_b_x96: SQRT_PRICE_B,
+        fee: FEE,
+    }
+}
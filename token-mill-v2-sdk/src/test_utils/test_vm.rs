@@ -12,11 +12,12 @@ use litesvm_token::{
 };
 use solana_sdk::{
     clock::Clock, instruction::Instruction, message::Message, native_token::sol_str_to_lamports,
-    program_pack::Pack, pubkey, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer,
     system_instruction::create_account, transaction::Transaction,
 };
 
 use super::constants::*;
+use crate::instructions::get_associated_token_address;
 
 pub fn get_vm(actors: Vec<Pubkey>) -> LiteSVM {
     let mut svm = LiteSVM::new()
@@ -31,18 +32,28 @@ pub fn get_vm(actors: Vec<Pubkey>) -> LiteSVM {
 
     svm.add_program_from_file(
         token_mill_v2_client::programs::TOKEN_MILL_V2_ID,
-        "./src/test_utils/programs/token_mill_v2.so",
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/test_utils/programs/token_mill_v2.so"
+        ),
     )
     .unwrap();
 
-    svm.add_program_from_file(METADATA_PROGRAM, "./src/test_utils/programs/metadata.so")
-        .unwrap();
+    svm.add_program_from_file(
+        METADATA_PROGRAM,
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/test_utils/programs/metadata.so"
+        ),
+    )
+    .unwrap();
 
     set_clock(&mut svm, CLOCK);
 
     svm
 }
 
+#[allow(clippy::result_large_err)]
 pub fn execute_instructions(
     svm: &mut LiteSVM,
     instructions: Vec<Instruction>,
@@ -115,7 +126,7 @@ pub fn create_tokens<const N: usize>(
         for token in &addresses {
             let set_authority_action =
                 SetAuthority::new(svm, &payer, token, AuthorityType::MintTokens)
-                    .new_authority(&authority);
+                    .new_authority(authority);
 
             set_authority_action.owner(&payer).send().unwrap();
         }
@@ -138,7 +149,7 @@ pub fn create_mint(
 
     let ix2 = spl_token::instruction::initialize_mint2(
         &spl_token::ID,
-        &address,
+        address,
         authority.unwrap_or(&ALICE),
         None,
         decimals.unwrap_or(8),
@@ -164,15 +175,7 @@ pub fn create_atas(svm: &mut LiteSVM, mints: Vec<Pubkey>, owners: Vec<Pubkey>) {
 }
 
 pub fn get_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
-    Pubkey::find_program_address(
-        &[
-            &owner.to_bytes(),
-            &spl_token::ID.to_bytes(),
-            &mint.to_bytes(),
-        ],
-        &pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
-    )
-    .0
+    get_associated_token_address(owner, mint)
 }
 
 pub fn get_token_balances<const N: usize>(
@@ -181,18 +184,30 @@ pub fn get_token_balances<const N: usize>(
     mint: [&Pubkey; N],
 ) -> [u64; N] {
     mint.map(|mint| {
-        get_spl_account::<Account>(&svm, &get_ata(owner, mint))
+        get_spl_account::<Account>(svm, &get_ata(owner, mint))
             .unwrap()
             .amount
     })
 }
 
 pub fn get_token_balance(svm: &LiteSVM, owner: &Pubkey, mint: &Pubkey) -> u64 {
-    get_spl_account::<Account>(&svm, &get_ata(owner, mint))
+    get_spl_account::<Account>(svm, &get_ata(owner, mint))
         .unwrap()
         .amount
 }
 
+/// Overwrites the balance of an existing token account, without minting
+pub fn set_token_balance(svm: &mut LiteSVM, owner: &Pubkey, mint: &Pubkey, amount: u64) {
+    let address = get_ata(owner, mint);
+    let mut account = svm.get_account(&address).unwrap();
+
+    let mut token_account = Account::unpack(&account.data).unwrap();
+    token_account.amount = amount;
+    Account::pack(token_account, &mut account.data).unwrap();
+
+    svm.set_account(address, account).unwrap();
+}
+
 pub fn make_address(string: &str) -> Pubkey {
     assert!(
         string.len() <= 32,
@@ -1,6 +1,7 @@
-use anyhow::Result;
 use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
 
+use crate::error::{Result, SdkError};
+
 const GET_KEYPAIR_URL: &str = "https://sol-barn.tokenmill.xyz/v2/keypairs/available";
 const SIGN_MARKET_CREATION_URL: &str =
     "https://sol-barn.tokenmill.xyz/v2/keypairs/sign-transaction";
@@ -20,7 +21,7 @@ pub fn get_vanity_address() -> Result<Pubkey> {
     let json: serde_json::Value = serde_json::from_str(&text)?;
     let pubkey_str = json["id"]
         .as_str()
-        .ok_or_else(|| anyhow::anyhow!("pubkey not found in response"))?;
+        .ok_or_else(|| SdkError::Vanity("pubkey not found in response".to_string()))?;
 
     let pubkey = Pubkey::try_from(pubkey_str)?;
 
@@ -28,7 +29,7 @@ pub fn get_vanity_address() -> Result<Pubkey> {
 }
 
 pub fn sign_market_creation_with_vanity(tx: &mut Transaction) -> Result<()> {
-    let serialized_tx = bincode::serialize(tx).unwrap();
+    let serialized_tx = bincode::serialize(tx)?;
     let serialized_tx_base58 = bs58::encode(&serialized_tx).into_string();
 
     let client = reqwest::blocking::ClientBuilder::new()
@@ -46,7 +47,7 @@ pub fn sign_market_creation_with_vanity(tx: &mut Transaction) -> Result<()> {
     let json: serde_json::Value = serde_json::from_str(&text)?;
     let signed_tx_base58 = json["transaction"]
         .as_str()
-        .ok_or_else(|| anyhow::anyhow!("transaction not found in response"))?;
+        .ok_or_else(|| SdkError::Vanity("transaction not found in response".to_string()))?;
 
     let signed_tx =
         bincode::deserialize::<Transaction>(&bs58::decode(signed_tx_base58).into_vec()?)?;